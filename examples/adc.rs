@@ -23,7 +23,7 @@ fn main() -> ! {
     let pmm = Pmm::new(periph.PMM);
     let port1 = Batch::new(periph.P1).split(&pmm);
     let mut led = port1.pin0.to_output();
-    let mut adc_pin = port1.pin1.to_alternate3();
+    let mut adc_pin = port1.pin1.to_analog();
 
     // ADC setup
     let mut adc = AdcConfig::new(
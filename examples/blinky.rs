@@ -30,7 +30,8 @@ fn main() -> ! {
     let (_smclk, _aclk, mut delay) = ClockConfig::new(periph.CS)
         .mclk_dcoclk(DcoclkFreqSel::_8MHz, MclkDiv::_1)
         .smclk_on(SmclkDiv::_1)
-        .freeze(&mut fram);
+        .freeze(&mut fram)
+        .unwrap();
 
     loop {
         // `toggle()` returns a `Result` because of embedded_hal, but the result is always `Ok` with MSP430 GPIO.
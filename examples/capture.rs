@@ -37,7 +37,8 @@ fn main() -> ! {
         .mclk_dcoclk(DcoclkFreqSel::_1MHz, MclkDiv::_1)
         .smclk_on(SmclkDiv::_1)
         .aclk_vloclk()
-        .freeze(&mut fram);
+        .freeze(&mut fram)
+        .unwrap();
 
     let mut tx = SerialConfig::new(
         periph.E_USCI_A1,
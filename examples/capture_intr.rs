@@ -57,7 +57,8 @@ fn main() -> ! {
         .mclk_dcoclk(DcoclkFreqSel::_1MHz, MclkDiv::_1)
         .smclk_on(SmclkDiv::_1)
         .aclk_vloclk()
-        .freeze(&mut fram);
+        .freeze(&mut fram)
+        .unwrap();
 
     let captures = CaptureParts3::config(periph.TB0, TimerConfig::aclk(&aclk))
         .config_cap1_input_A(p1.pin6.to_alternate2())
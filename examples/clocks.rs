@@ -33,7 +33,8 @@ fn main() -> ! {
         .mclk_dcoclk(DcoclkFreqSel::_8MHz, MclkDiv::_1)
         .smclk_on(SmclkDiv::_1)
         .aclk_vloclk()
-        .freeze(&mut fram);
+        .freeze(&mut fram)
+        .unwrap();
 
     const DELAY: WdtClkPeriods = WdtClkPeriods::_8192K;
 
@@ -32,7 +32,8 @@ fn main() -> ! {
             .mclk_dcoclk(DcoclkFreqSel::_1MHz, MclkDiv::_1)
             .smclk_on(SmclkDiv::_2)
             .aclk_refoclk()
-            .freeze(&mut fram);
+            .freeze(&mut fram)
+            .unwrap();
 
         let pmm = Pmm::new(periph.PMM);
         let mut led = Batch::new(periph.P1).split(&pmm).pin0.to_output();
@@ -63,6 +64,7 @@ fn main() -> ! {
                         RecvError::Parity => '!',
                         RecvError::Overrun(_) => '}',
                         RecvError::Framing => '?',
+                        RecvError::Break => '#',
                     }) as u8
                 }
             };
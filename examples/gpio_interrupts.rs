@@ -33,7 +33,8 @@ fn main() -> ! {
         // 32 KHz SMCLK
         .smclk_on(SmclkDiv::_2)
         .aclk_vloclk()
-        .freeze(&mut Fram::new(periph.FRCTL));
+        .freeze(&mut Fram::new(periph.FRCTL))
+        .unwrap();
     let mut wdt = Wdt::constrain(periph.WDT_A).to_interval();
     let pmm = Pmm::new(periph.PMM);
 
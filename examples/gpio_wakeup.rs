@@ -0,0 +1,73 @@
+#![no_main]
+#![no_std]
+#![feature(abi_msp430_interrupt)]
+
+use critical_section::with;
+use msp430fr2355::interrupt;
+
+use core::cell::RefCell;
+use embedded_hal::digital::v2::*;
+use msp430::interrupt::Mutex;
+use msp430_rt::entry;
+use msp430fr2x5x_hal::{
+    gpio::{Batch, Edge, GpioVector, Output, Pin, Pin0, PxIV, P1, P2},
+    lpm,
+    pmm::Pmm,
+    watchdog::Wdt,
+};
+use panic_msp430 as _;
+
+static RED_LED: Mutex<RefCell<Option<Pin<P1, Pin0, Output>>>> = Mutex::new(RefCell::new(None));
+static P2IV: Mutex<RefCell<Option<PxIV<P2>>>> = Mutex::new(RefCell::new(None));
+
+// The CPU sleeps in LPM4 with all clocks off. Pressing the P2.3 button wakes it up to toggle the
+// red LED, after which it goes back to sleep.
+#[entry]
+fn main() -> ! {
+    let periph = msp430fr2355::Peripherals::take().unwrap();
+    let _wdt = Wdt::constrain(periph.WDT_A);
+    let pmm = Pmm::new(periph.PMM);
+
+    let p1 = Batch::new(periph.P1)
+        .config_pin0(|p| p.to_output())
+        .split(&pmm);
+    let p2 = Batch::new(periph.P2)
+        .config_pin3(|p| p.pullup())
+        .split(&pmm);
+
+    let mut button = p2.pin3;
+    button.enable_wakeup(Edge::Falling);
+
+    with(|cs| RED_LED.borrow_ref_mut(cs).replace(p1.pin0));
+    with(|cs| P2IV.borrow_ref_mut(cs).replace(p2.pxiv));
+
+    loop {
+        // Interrupts are enabled on entry, and the CPU stays asleep after the ISR returns
+        lpm::enter_lpm4();
+    }
+}
+
+#[interrupt]
+fn PORT2() {
+    with(|cs| {
+        let Some(ref mut red_led) = *RED_LED.borrow_ref_mut(cs) else {
+            return;
+        };
+        let Some(ref mut p2iv) = *P2IV.borrow_ref_mut(cs) else {
+            return;
+        };
+
+        // Reading the vector clears the pin's interrupt flag
+        if let GpioVector::Pin3Isr = p2iv.get_interrupt_vector() {
+            red_led.toggle().ok();
+        }
+    });
+}
+
+// The compiler will emit calls to the abort() compiler intrinsic if debug assertions are
+// enabled (default for dev profile). MSP430 does not actually have meaningful abort() support
+// so for now, we create our own in each application where debug assertions are present.
+#[no_mangle]
+extern "C" fn abort() -> ! {
+    panic!();
+}
@@ -25,7 +25,8 @@ fn main() -> ! {
         .mclk_dcoclk(DcoclkFreqSel::_8MHz, MclkDiv::_1)
         .smclk_on(SmclkDiv::_1)
         .aclk_vloclk()
-        .freeze(&mut fram);
+        .freeze(&mut fram)
+        .unwrap();
 
     let mut i2c = I2CBusConfig::new(periph.E_USCI_B1, GlitchFilter::Max50ns)
         .use_smclk(&smclk, 80) // 8MHz / 10 = 100kHz
@@ -58,7 +58,8 @@ fn main() -> ! {
         .mclk_dcoclk(DcoclkFreqSel::_4MHz, MclkDiv::_1)
         .smclk_on(SmclkDiv::_2)
         .aclk_refoclk()
-        .freeze(&mut fram);
+        .freeze(&mut fram)
+        .unwrap();
 
     let pmm = Pmm::new(periph.PMM);
     let p1 = Batch::new(periph.P1).split(&pmm);
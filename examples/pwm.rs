@@ -28,11 +28,12 @@ fn main() -> ! {
         .mclk_dcoclk(DcoclkFreqSel::_1MHz, MclkDiv::_1)
         .smclk_on(SmclkDiv::_1)
         .aclk_vloclk()
-        .freeze(&mut fram);
+        .freeze(&mut fram)
+        .unwrap();
 
     let pwm = PwmParts7::new(periph.TB3, TimerConfig::smclk(&smclk), 5000);
-    let mut pwm4 = pwm.pwm4.init(p6.pin3.to_output().to_alternate1());
-    let mut pwm5 = pwm.pwm5.init(p6.pin4.to_output().to_alternate1());
+    let mut pwm4 = pwm.pwm4.init(p6.pin3.into_pwm_output());
+    let mut pwm5 = pwm.pwm5.init(p6.pin4.into_pwm_output());
 
     config_pwm(&mut pwm4, 100);
     config_pwm(&mut pwm5, 3795);
@@ -37,7 +37,8 @@ fn main() -> ! {
         .mclk_refoclk(MclkDiv::_1)
         .smclk_on(SmclkDiv::_1)
         .aclk_vloclk()
-        .freeze(&mut Fram::new(periph.FRCTL));
+        .freeze(&mut Fram::new(periph.FRCTL))
+        .unwrap();
 
     let mut rtc = Rtc::new(periph.RTC).use_vloclk();
     rtc.set_clk_div(RtcDiv::_10);
@@ -31,7 +31,8 @@ fn main() -> ! {
         .mclk_dcoclk(DcoclkFreqSel::_8MHz, MclkDiv::_1)
         .smclk_on(SmclkDiv::_1)
         .aclk_vloclk()
-        .freeze(&mut fram);
+        .freeze(&mut fram)
+        .unwrap();
 
     let mut spi = SpiBusConfig::new(periph.E_USCI_A0, MODE_0, true)
         .use_smclk(&smclk, 16) // 8MHz / 16 = 500kHz
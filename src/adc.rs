@@ -8,16 +8,33 @@
 //! 
 //! As a convenience, `.read_voltage_mv()` combines `.read()` and `.count_to_mv()`.
 //! 
-//! Currently the only supported ADC voltage reference is `AVCC`, the operating voltage of the MSP430.
+//! By default the ADC converts relative to `AVCC`, the operating voltage of the MSP430. External references applied to the
+//! VeREF+ and VeREF- pins can be selected with `AdcConfig::reference()`, either together or each on its own.
+//! The 1.5V, 2.0V or 2.5V internal reference can also be selected by handing over an `InternalVRef` from the `Pmm`. 
+//! Since its voltage is known, `.reference_mv()` and `.read_mv()` then convert counts without needing the reference voltage.
 //! 
-//! The ADC may read from any of the following pins:
+//! The ADC may read from any of the following pins, once they have been put into analog mode with `.to_analog()`:
 //!
 //! P1.0 - P1.7 (channels 0 to 7), P5.0 - P5.3 (channels 8 to 11).
 //! 
 //! ADC channels 12 to 15 are not associated with external pins, so in lieu of a pin use the `static`s below.
 //!
+//! Instead of polling, the conversion complete interrupt can be enabled with `.enable_interrupts()`. The ADC's ISR then calls 
+//! `.interrupt_source()` to find out which event fired.
+//! 
+//! The window comparator, set up with `.configure_window()`, raises interrupts when a result falls above, below or inside 
+//! a pair of thresholds, making the ADC a cheap threshold detector.
+//! 
+//! Several channels can be converted in one go with `.read_sequence()`, which uses the ADC's sequence-of-channels mode.
+//! 
+//! With the temperature sensor turned on through the `Pmm` and the 1.5V internal reference selected, `.read_temperature_c()` 
+//! converts readings of channel 12 into degrees Celsius using the factory calibration in the TLV.
+//! 
+//! For continuous sampling, `Adc::start_streaming()` repeatedly converts one channel in the background. The ADC's ISR
+//! moves each result into a ring buffer, which the main loop drains with `AdcStream::take_samples()`.
+//!
 
-use crate::{clock::{Aclk, Smclk}, gpio::*};
+use crate::{clock::{Aclk, Clock, Smclk}, gpio::*, pmm::{InternalVRef, TempSensor}, tlv};
 use core::convert::Infallible;
 use embedded_hal::adc::{Channel, OneShot};
 use msp430fr2355::ADC;
@@ -61,6 +78,25 @@ impl SampleTime {
     fn adcsht(self) -> u8 {
         self as u8
     }
+
+    #[inline(always)]
+    fn cycles(self) -> u32 {
+        match self {
+            SampleTime::_4 => 4,
+            SampleTime::_8 => 8,
+            SampleTime::_16 => 16,
+            SampleTime::_32 => 32,
+            SampleTime::_64 => 64,
+            SampleTime::_96 => 96,
+            SampleTime::_128 => 128,
+            SampleTime::_192 => 192,
+            SampleTime::_256 => 256,
+            SampleTime::_384 => 384,
+            SampleTime::_512 => 512,
+            SampleTime::_768 => 768,
+            SampleTime::_1024 => 1024,
+        }
+    }
 }
 
 /// How much the ADC input clock will be divided by after being divided by the predivider
@@ -92,6 +128,11 @@ impl ClockDivider {
     fn adcdiv(self) -> u8 {
         self as u8
     }
+
+    #[inline(always)]
+    fn divisor(self) -> u32 {
+        self as u32 + 1
+    }
 }
 
 #[derive(Default, Copy, Clone, PartialEq, Eq)]
@@ -131,6 +172,15 @@ impl Predivider {
     fn adcpdiv(self) -> u8 {
         self as u8
     }
+
+    #[inline(always)]
+    fn divisor(self) -> u32 {
+        match self {
+            Predivider::_1 => 1,
+            Predivider::_4 => 4,
+            Predivider::_64 => 64,
+        }
+    }
 }
 
 /// The output resolution of the ADC conversion. Also determines how many ADCCLK cycles the conversion step takes.
@@ -152,6 +202,15 @@ impl Resolution {
     fn adcres(self) -> u8 {
         self as u8
     }
+
+    #[inline(always)]
+    fn conversion_cycles(self) -> u32 {
+        match self {
+            Resolution::_8BIT => 10,
+            Resolution::_10BIT => 12,
+            Resolution::_12BIT => 14,
+        }
+    }
 }
 
 /// Selects the drive capability of the ADC reference buffer, which can increase the maximum sampling speed at the cost of increased power draw.
@@ -176,10 +235,107 @@ impl SamplingRate {
     }
 }
 
+/// The format of conversion results as stored in the ADC conversion memory register.
+/// 
+/// Default: Unsigned
+#[derive(Default, Copy, Clone, PartialEq, Eq)]
+pub enum DataFormat {
+    /// Unsigned binary, right-justified. 0V reads as 0 and full scale reads as the maximum count for the resolution.
+    #[default]
+    Unsigned,
+    /// Signed 2's complement, left-justified. Midscale reads as 0 and the result occupies the top bits of the register regardless of resolution.
+    Signed,
+}
+
+impl DataFormat {
+    #[inline(always)]
+    fn adcdf(self) -> bool {
+        match self {
+            DataFormat::Unsigned => false,
+            DataFormat::Signed => true,
+        }
+    }
+}
+
+/// Voltage references used as the upper and lower limits of ADC conversions.
+/// 
+/// The upper (VR+) and lower (VR-) limits are selected independently in hardware, giving these combinations:
+/// 
+/// | Variant             | VR+     | VR-     |
+/// |---------------------|---------|---------|
+/// | `Avcc`              | AVCC    | AVSS    |
+/// | `Internal`          | VREF    | AVSS    |
+/// | `VerefPos`          | VeREF+  | AVSS    |
+/// | `ExternalVeref`     | VeREF+  | VeREF-  |
+/// | `AvccVerefNeg`      | AVCC    | VeREF-  |
+/// | `InternalVerefNeg`  | VREF    | VeREF-  |
+/// 
+/// Using VeREF- as the lower limit lets the ADC measure relative to a separate analog ground, which improves accuracy in mixed-signal layouts.
+/// 
+/// Default: AVCC and AVSS
+pub enum AdcRef {
+    /// AVCC as the upper limit and AVSS as the lower limit
+    Avcc,
+    /// The internal shared reference as the upper limit and AVSS as the lower limit.
+    /// The `Pmm` only hands out the token once the reference has settled, so conversions can start straight away.
+    Internal {
+        /// Internal reference token, kept by the ADC so the reference stays on at the same voltage
+        vref: InternalVRef,
+    },
+    /// The internal shared reference as the upper limit and the VeREF- pin (P1.2) as the lower limit
+    InternalVerefNeg {
+        /// Internal reference token, kept by the ADC so the reference stays on at the same voltage
+        vref: InternalVRef,
+        /// VeREF- pin
+        neg_pin: Pin<P1, Pin2, Analog>,
+    },
+    /// AVCC as the upper limit and the VeREF- pin (P1.2) as the lower limit
+    AvccVerefNeg {
+        /// VeREF- pin
+        neg_pin: Pin<P1, Pin2, Analog>,
+    },
+    /// The VeREF+ pin (P1.0) as the upper limit and AVSS as the lower limit
+    VerefPos {
+        /// VeREF+ pin
+        pos_pin: Pin<P1, Pin0, Analog>,
+    },
+    /// External references applied to the VeREF+ pin (P1.0) as the upper limit and the VeREF- pin (P1.2) as the lower limit.
+    /// Provides the most accurate conversions when a precision external reference is available.
+    ExternalVeref {
+        /// VeREF+ pin
+        pos_pin: Pin<P1, Pin0, Analog>,
+        /// VeREF- pin
+        neg_pin: Pin<P1, Pin2, Analog>,
+    },
+}
+
+impl AdcRef {
+    #[inline(always)]
+    fn adcsref(&self) -> u8 {
+        match self {
+            AdcRef::Avcc => 0b000,
+            AdcRef::Internal { .. } => 0b001,
+            AdcRef::VerefPos { .. } => 0b010,
+            AdcRef::AvccVerefNeg { .. } => 0b100,
+            AdcRef::InternalVerefNeg { .. } => 0b101,
+            AdcRef::ExternalVeref { .. } => 0b110,
+        }
+    }
+
+    /// Voltage of the upper limit in millivolts, if it's known
+    #[inline(always)]
+    fn ref_mv(&self) -> Option<u16> {
+        match self {
+            AdcRef::Internal { vref } | AdcRef::InternalVerefNeg { vref, .. } => Some(vref.voltage().millivolts()),
+            _ => None,
+        }
+    }
+}
+
 // Pins corresponding to an ADC channel. Pin types can have `::channel()` called on them to get their ADC channel index.
 macro_rules! impl_adc_channel_pin {
     ($port: ty, $pin: ty, $channel: literal ) => {
-        impl Channel<Adc> for Pin<$port, $pin, Alternate3<Input<Floating>>> {
+        impl Channel<Adc> for Pin<$port, $pin, Analog> {
             type ID = u8;
 
             fn channel() -> Self::ID {
@@ -243,18 +399,20 @@ pub static ADC_CH15_VCC: AdcVccChannel = AdcVccChannel;
 /// Typestate for an ADC configuration with no clock source selected
 pub struct NoClockSet;
 /// Typestate for an ADC configuration with a clock source selected
-pub struct ClockSet(ClockSource);
+pub struct ClockSet(ClockSource, u32);
+
+/// Typical MODCLK frequency. The actual frequency varies between devices and with temperature.
+const MODCLK_TYP_HZ: u32 = 4_800_000;
 
 /// Configuration object for an ADC.
 /// 
-/// Currently the only supported voltage reference is AVCC.
-/// 
 /// The default configuration is based on the default register values:
 /// - Predivider = 1 and clock divider = 1
 /// - 10-bit resolution
 /// - 8 cycle sample time
 /// - Max 200 ksps sample rate
-#[derive(Clone, PartialEq, Eq)]
+/// - Unsigned, right-justified results
+/// - AVCC and AVSS references
 pub struct AdcConfig<STATE> {
     state: STATE,
     /// How much the input clock is divided by, after the predivider.
@@ -267,6 +425,10 @@ pub struct AdcConfig<STATE> {
     pub sampling_rate: SamplingRate,
     /// Determines the number of ADCCLK cycles the sampling time takes.
     pub sample_time: SampleTime,
+    /// The format of results in the conversion memory register. Set with `.data_format()`.
+    pub data_format: DataFormat,
+    // Set with `.reference()`. Held onto so the pins and reference token stay with the ADC.
+    reference: AdcRef,
 }
 
 // Only implement Default for NoClockSet
@@ -279,6 +441,8 @@ impl Default for AdcConfig<NoClockSet> {
             resolution: Default::default(), 
             sampling_rate: Default::default(), 
             sample_time: Default::default(), 
+            data_format: Default::default(),
+            reference: AdcRef::Avcc,
         }
     }
 }
@@ -299,42 +463,74 @@ impl AdcConfig<NoClockSet> {
             resolution,
             sampling_rate,
             sample_time,
+            data_format: Default::default(),
+            reference: AdcRef::Avcc,
         }
     }
     /// Configure the ADC to use SMCLK
-    pub fn use_smclk(self, _smclk: &Smclk) -> AdcConfig<ClockSet>{
+    pub fn use_smclk(self, smclk: &Smclk) -> AdcConfig<ClockSet>{
         AdcConfig { 
-            state: ClockSet(ClockSource::SmClk),
+            state: ClockSet(ClockSource::SmClk, smclk.freq()),
             clock_divider: self.clock_divider, 
             predivider: self.predivider, 
             resolution: self.resolution, 
             sampling_rate: self.sampling_rate, 
             sample_time: self.sample_time, 
+            data_format: self.data_format,
+            reference: self.reference,
         }
     }
     /// Configure the ADC to use ACLK
-    pub fn use_aclk(self, _aclk: &Aclk) -> AdcConfig<ClockSet>{
+    pub fn use_aclk(self, aclk: &Aclk) -> AdcConfig<ClockSet>{
         AdcConfig { 
-            state: ClockSet(ClockSource::AClk),
+            state: ClockSet(ClockSource::AClk, aclk.freq() as u32),
             clock_divider: self.clock_divider, 
             predivider: self.predivider, 
             resolution: self.resolution, 
             sampling_rate: self.sampling_rate, 
             sample_time: self.sample_time, 
+            data_format: self.data_format,
+            reference: self.reference,
         }
     }
     /// Configure the ADC to use MODCLK
     pub fn use_modclk(self) -> AdcConfig<ClockSet>{
         AdcConfig { 
-            state: ClockSet(ClockSource::ModClk),
+            state: ClockSet(ClockSource::ModClk, MODCLK_TYP_HZ),
             clock_divider: self.clock_divider, 
             predivider: self.predivider, 
             resolution: self.resolution, 
             sampling_rate: self.sampling_rate, 
             sample_time: self.sample_time, 
+            data_format: self.data_format,
+            reference: self.reference,
+            sequence: None,
         }
     }
 }
+impl<STATE> AdcConfig<STATE> {
+    /// Set the format of results in the conversion memory register. 
+    /// 
+    /// `Adc::adc_get_result()` always returns an unsigned, right-justified count regardless of this setting, 
+    /// whereas `Adc::adc_get_raw_result()` returns the register contents in this format.
+    pub fn data_format(mut self, data_format: DataFormat) -> Self {
+        self.data_format = data_format;
+        self
+    }
+
+    /// Select the voltage references of the ADC. External references consume the pins they're applied to, 
+    /// which must already be in analog mode. The internal reference consumes its `InternalVRef` token, so it stays on.
+    /// The ADC holds onto the reference until `Adc::free()` hands it back. Only call this once, since a reference 
+    /// replaced by a later call is dropped and can't be handed back.
+    /// 
+    /// `count_to_mv()` must be passed the voltage between the selected references. With the internal reference 
+    /// the voltage is known, so `Adc::read_mv()` can be used instead.
+    pub fn reference(mut self, reference: AdcRef) -> Self {
+        self.reference = reference;
+        self
+    }
+}
+
 impl AdcConfig<ClockSet> {
     /// Applies this ADC configuration to hardware registers, and returns an ADC.
     pub fn configure(self, mut adc_reg: ADC) -> Adc {
@@ -344,6 +540,9 @@ impl AdcConfig<ClockSet> {
         let adcsht = self.sample_time.adcsht();
         adc_reg.adcctl0.write(|w| w.adcsht().bits(adcsht));
 
+        let adcsref = self.reference.adcsref();
+        adc_reg.adcmctl0.write(|w| w.adcsref().bits(adcsref));
+
         let adcssel = self.state.0.adcssel();
         let adcdiv = self.clock_divider.adcdiv();
         adc_reg.adcctl1.write(|w| {w
@@ -355,23 +554,71 @@ impl AdcConfig<ClockSet> {
         let adcpdiv = self.predivider.adcpdiv();
         let adcres = self.resolution.adcres();
         let adcsr = self.sampling_rate.adcsr();
+        let adcdf = self.data_format.adcdf();
         adc_reg.adcctl2.write(|w| { w
             .adcpdiv().bits(adcpdiv)
             .adcres().bits(adcres)
+            .adcdf().bit(adcdf)
             .adcsr().bit(adcsr)
         });
 
         Adc {
             adc_reg,
             is_waiting: false,
+            data_format: self.data_format,
+            conversion_cycles: self.sample_time.cycles() + self.resolution.conversion_cycles(),
+            adcclk_hz: self.state.1 / (self.predivider.divisor() * self.clock_divider.divisor()),
+            reference: self.reference,
         }
     }
 }
 
+/// Errors reported by ADC methods
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdcError {
+    /// The voltage of the selected reference isn't known to the HAL
+    UnknownReference,
+    /// The channel range is empty or includes a channel above 15
+    InvalidChannels,
+    /// The output buffer length doesn't match the number of channels
+    BufferLength,
+    /// The low threshold is above the high threshold, or a threshold is above the maximum count for the resolution
+    InvalidWindow,
+    /// The temperature sensor calibration requires the 1.5V internal reference, but a different reference is selected
+    WrongReference,
+    /// The temperature sensor calibration constants are missing from the TLV or are invalid
+    NoCalibration,
+}
+
+/// Indicates which event caused the ADC interrupt to fire
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdcVector {
+    /// No pending interrupt
+    NoInterrupt,
+    /// A conversion result was overwritten before it was read
+    Overflow,
+    /// A conversion was requested before the previous one finished
+    TimeOverflow,
+    /// The conversion result is above the window comparator's high threshold
+    Above,
+    /// The conversion result is below the window comparator's low threshold
+    Below,
+    /// The conversion result is inside the window comparator's thresholds
+    Inside,
+    /// A conversion has completed and its result is ready to be read
+    ConversionComplete,
+}
+
 /// Controls the onboard ADC. The `read()` method is available through the embedded_hal `OneShot` trait.
 pub struct Adc {
     adc_reg: ADC,
     is_waiting: bool,
+    data_format: DataFormat,
+    conversion_cycles: u32,
+    adcclk_hz: u32,
+    reference: AdcRef,
+    // In sequence mode, the channel of the next result and the channel the sequence starts from
+    sequence: Option<(u8, u8)>,
 }
 
 impl Adc {
@@ -380,11 +627,170 @@ impl Adc {
         self.adc_reg.adcctl1.read().adcbusy().bit_is_set()
     }
 
-    /// Gets the latest ADC conversion result.
+    /// Gets the latest ADC conversion result as an unsigned, right-justified count, regardless of the configured data format.
     pub fn adc_get_result(&self) -> u16 {
+        let raw = self.adc_get_raw_result();
+        match self.data_format {
+            DataFormat::Unsigned => raw,
+            // Undo the left-justification, then flipping the sign bit moves midscale back up from 0
+            DataFormat::Signed => {
+                let bits = self.resolution_bits();
+                (raw >> (16 - bits)) ^ (1 << (bits - 1))
+            }
+        }
+    }
+
+    /// Gets the latest ADC conversion result as a right-justified count relative to midscale, regardless of the configured data format.
+    pub fn adc_get_result_signed(&self) -> i16 {
+        let raw = self.adc_get_raw_result();
+        let bits = self.resolution_bits();
+        match self.data_format {
+            DataFormat::Unsigned => raw as i16 - (1 << (bits - 1)),
+            DataFormat::Signed => (raw as i16) >> (16 - bits),
+        }
+    }
+
+    /// Gets the latest ADC conversion result exactly as stored in the conversion memory register, in the configured data format.
+    pub fn adc_get_raw_result(&self) -> u16 {
         self.adc_reg.adcmem0.read().bits()
     }
 
+    /// The number of bits in a conversion result, based on the configured resolution.
+    fn resolution_bits(&self) -> u8 {
+        use crate::pac::adc::adcctl2::ADCRES_A;
+        match self.adc_reg.adcctl2.read().adcres().variant() {
+            ADCRES_A::ADCRES_0 => 8,
+            ADCRES_A::ADCRES_1 => 10,
+            ADCRES_A::ADCRES_2 => 12,
+            ADCRES_A::ADCRES_3 => 12, // Reserved, unreachable
+        }
+    }
+
+    /// The channel of the next conversion result.
+    /// 
+    /// In sequence mode (such as during `.read_sequence()`) the hardware steps through the channels without reporting which 
+    /// one it's on, so the channel is tracked in software instead, counting down from the first channel of the sequence as 
+    /// results are read with `.adc_get_result_with_channel()`.
+    pub fn current_channel(&self) -> u8 {
+        match self.sequence {
+            Some((next, _)) => next,
+            None => self.adc_reg.adcmctl0.read().adcinch().bits(),
+        }
+    }
+
+    /// Gets the latest ADC conversion result along with the channel it was sampled from, as a `(channel, count)` pair.
+    /// 
+    /// This is useful when a single interrupt handler services conversions on several channels and needs to route each reading,
+    /// including the results of a sequence of channels. In sequence mode every result must be read through this method,
+    /// so that the tracked channel stays in step with the hardware.
+    pub fn adc_get_result_with_channel(&mut self) -> (u8, u16) {
+        let ch = self.current_channel();
+        if let Some((next, start)) = self.sequence.as_mut() {
+            // The sequence counts down to channel 0, then starts over if it repeats
+            *next = if *next == 0 { *start } else { *next - 1 };
+        }
+        (ch, self.adc_get_result())
+    }
+
+    /// Number of ADCCLK cycles taken by a single conversion, including both the sample-and-hold and conversion steps.
+    pub fn conversion_cycles(&self) -> u32 {
+        self.conversion_cycles
+    }
+
+    /// Time taken by a single conversion in microseconds, rounded up. 
+    /// 
+    /// When using MODCLK this is based on its typical frequency of 4.8MHz, so the actual time may differ by up to 25%.
+    pub fn conversion_time_us(&self) -> u32 {
+        let hz = self.adcclk_hz.max(1);
+        (self.conversion_cycles * 1_000_000).div_ceil(hz)
+    }
+
+    /// Enables the conversion complete interrupt, which fires each time a result is ready.
+    pub fn enable_interrupts(&mut self) {
+        unsafe {
+            self.adc_reg.adcifg.clear_bits(|w| w.adcifg0().clear_bit());
+            self.adc_reg.adcie.set_bits(|w| w.adcie0().set_bit());
+        }
+    }
+
+    /// Disables the conversion complete interrupt.
+    pub fn disable_interrupts(&mut self) {
+        unsafe {
+            self.adc_reg.adcie.clear_bits(|w| w.adcie0().clear_bit());
+        }
+    }
+
+    /// Read the ADC interrupt vector, which reports the highest priority pending interrupt. 
+    /// Reading the vector automatically clears the corresponding interrupt flag.
+    /// 
+    /// Clearing the flag doesn't discard the result, so calling `.adc_get_result()` after `ConversionComplete` is safe.
+    pub fn interrupt_source(&mut self) -> AdcVector {
+        match self.adc_reg.adciv.read().bits() {
+            0x00 => AdcVector::NoInterrupt,
+            0x02 => AdcVector::Overflow,
+            0x04 => AdcVector::TimeOverflow,
+            0x06 => AdcVector::Above,
+            0x08 => AdcVector::Below,
+            0x0A => AdcVector::Inside,
+            0x0C => AdcVector::ConversionComplete,
+            _ => unsafe { core::hint::unreachable_unchecked() },
+        }
+    }
+
+    /// Configure the window comparator and enable its interrupts. After each conversion, `.interrupt_source()` reports 
+    /// `Above`, `Below` or `Inside` depending on where the result lies relative to the thresholds.
+    /// 
+    /// `low` and `high` are unsigned, right-justified counts at the configured resolution, like the results of `.adc_get_result()`, 
+    /// and are converted to the configured data format. Any conversion in progress is abandoned.
+    pub fn configure_window(&mut self, low: u16, high: u16) -> Result<(), AdcError> {
+        let max = (1u16 << self.resolution_bits()) - 1;
+        if low > high || high > max {
+            return Err(AdcError::InvalidWindow);
+        }
+        let (low, high) = (self.count_to_raw(low), self.count_to_raw(high));
+
+        let was_on = self.adc_reg.adcctl0.read().adcon().bit_is_set();
+        self.disable();
+        self.is_waiting = false;
+        self.adc_reg.adclo.write(|w| unsafe { w.bits(low) });
+        self.adc_reg.adchi.write(|w| unsafe { w.bits(high) });
+        unsafe {
+            self.adc_reg.adcifg.clear_bits(|w| w
+                .adchiifg().clear_bit()
+                .adcloifg().clear_bit()
+                .adcinifg().clear_bit());
+            self.adc_reg.adcie.set_bits(|w| w
+                .adchiie().set_bit()
+                .adcloie().set_bit()
+                .adcinie().set_bit());
+        }
+        if was_on {
+            self.enable();
+        }
+        Ok(())
+    }
+
+    /// Disable the window comparator interrupts.
+    pub fn disable_window(&mut self) {
+        unsafe {
+            self.adc_reg.adcie.clear_bits(|w| w
+                .adchiie().clear_bit()
+                .adcloie().clear_bit()
+                .adcinie().clear_bit());
+        }
+    }
+
+    /// Converts an unsigned, right-justified count into the configured data format. The inverse of `.adc_get_result()`.
+    fn count_to_raw(&self, count: u16) -> u16 {
+        match self.data_format {
+            DataFormat::Unsigned => count,
+            DataFormat::Signed => {
+                let bits = self.resolution_bits();
+                (count ^ (1 << (bits - 1))) << (16 - bits)
+            }
+        }
+    }
+
     /// Enables this ADC, ready to start conversions.
     pub fn enable(&mut self) {
         unsafe {
@@ -397,6 +803,43 @@ impl Adc {
         disable_adc_reg(&mut self.adc_reg);
     }
 
+    /// Abandons any conversion in progress, so the next call to `.read()` starts a fresh conversion on any channel.
+    /// 
+    /// The ADC stays enabled. The result of the abandoned conversion is discarded.
+    pub fn cancel(&mut self) {
+        unsafe {
+            self.adc_reg.adcctl0.clear_bits(|w| w
+                .adcenc().clear_bit()
+                .adcsc().clear_bit());
+        }
+        self.is_waiting = false;
+    }
+
+    /// Escape hatch for ADC features that `AdcConfig` doesn't model, giving direct access to the ADC registers.
+    /// 
+    /// Many ADC settings can only be changed while the ADC is disabled, so the ADC is disabled before `f` runs, 
+    /// abandoning any conversion in progress, and re-enabled afterwards if it was enabled before.
+    /// 
+    /// The data format is re-read from the registers afterwards, but `conversion_cycles()` and `conversion_time_us()` 
+    /// keep reporting the originally configured sample time, resolution and clock.
+    /// Enabling interrupts or sequences here may conflict with the other methods on `Adc`.
+    pub fn modify_registers<F: FnOnce(&ADC)>(&mut self, f: F) {
+        let was_on = self.adc_reg.adcctl0.read().adcon().bit_is_set();
+        self.disable();
+        self.is_waiting = false;
+
+        f(&self.adc_reg);
+
+        self.data_format = if self.adc_reg.adcctl2.read().adcdf().bit_is_set() {
+            DataFormat::Signed
+        } else {
+            DataFormat::Unsigned
+        };
+        if was_on {
+            self.enable();
+        }
+    }
+
     /// Selects which pin to sample.
     fn set_pin<PIN>(&mut self, _pin: &PIN)
     where
@@ -418,16 +861,10 @@ impl Adc {
 
     /// Convert an ADC count to a voltage value in millivolts.
     /// 
+    /// `count` is an unsigned, right-justified count as returned by `.adc_get_result()` or `.read()`.
     /// `ref_voltage_mv` is the reference voltage of the ADC in millivolts.
     pub fn count_to_mv(&self, count: u16, ref_voltage_mv: u16) -> u16 {
-        use crate::pac::adc::adcctl2::ADCRES_A;
-        let resolution = match self.adc_reg.adcctl2.read().adcres().variant() {
-            ADCRES_A::ADCRES_0 => 256, // 8-bit
-            ADCRES_A::ADCRES_1 => 1024, // 10-bit
-            ADCRES_A::ADCRES_2 => 4096, // 12-bit
-            ADCRES_A::ADCRES_3 => 4096, // Reserved, unreachable
-        };
-        ((count as u32 * ref_voltage_mv as u32) / resolution) as u16
+        ((count as u32 * ref_voltage_mv as u32) >> self.resolution_bits()) as u16
     }
 
     /// Begins a single ADC conversion if one isn't already underway, enabling the ADC in the process.
@@ -438,6 +875,196 @@ impl Adc {
     pub fn read_voltage_mv<PIN: Channel<Self, ID = u8>>(&mut self, pin: &mut PIN, ref_voltage_mv: u16) -> nb::Result<u16, Infallible> {
         self.read(pin).map(|count| self.count_to_mv(count, ref_voltage_mv))
     }
+
+    /// The voltage of the ADC's upper reference in millivolts. Only known when the internal reference is selected.
+    pub fn reference_mv(&self) -> Option<u16> {
+        self.reference.ref_mv()
+    }
+
+    /// Like `.read_voltage_mv()`, but converts the result using the internal reference's voltage.
+    /// 
+    /// Returns `AdcError::UnknownReference` without starting a conversion if the internal reference isn't selected.
+    pub fn read_mv<PIN: Channel<Self, ID = u8>>(&mut self, pin: &mut PIN) -> nb::Result<u16, AdcError> {
+        let ref_mv = self.reference.ref_mv().ok_or(nb::Error::Other(AdcError::UnknownReference))?;
+        self.read(pin)
+            .map(|count| self.count_to_mv(count, ref_mv))
+            .map_err(|_| nb::Error::WouldBlock)
+    }
+
+    /// Turn off the ADC and give back the peripheral along with the selected reference, so its pins can be reused 
+    /// and an internal reference token can be returned to the `Pmm`.
+    pub fn free(mut self) -> (ADC, AdcRef) {
+        disable_adc_reg(&mut self.adc_reg);
+        (self.adc_reg, self.reference)
+    }
+}
+
+impl Adc {
+    /// Begins a conversion of the temperature sensor if one isn't already underway, returning the temperature in degrees Celsius
+    /// once it's done, otherwise returns `WouldBlock`. See `.read_temperature_centi_c()` for details.
+    pub fn read_temperature_c(&mut self, sensor: &TempSensor) -> nb::Result<i16, AdcError> {
+        self.read_temperature_centi_c(sensor).map(|centi| centi / 100)
+    }
+
+    /// Begins a conversion of the temperature sensor if one isn't already underway, returning the temperature in hundredths 
+    /// of a degree Celsius once it's done, otherwise returns `WouldBlock`. 
+    /// 
+    /// The reading is fitted to the factory readings at 30°C and 85°C stored in the TLV, which were taken against the 
+    /// 1.5V internal reference, so `AdcRef::Internal` must be selected with a 1.5V reference. The sensor needs a sample 
+    /// time of at least 30µs for accurate readings.
+    pub fn read_temperature_centi_c(&mut self, _sensor: &TempSensor) -> nb::Result<i16, AdcError> {
+        if self.reference.ref_mv() != Some(1500) {
+            return Err(nb::Error::Other(AdcError::WrongReference));
+        }
+        let cal = tlv::adc_calibration().ok_or(nb::Error::Other(AdcError::NoCalibration))?;
+        let (cal_30, cal_85) = (cal.temp_30c_1v5 as i32, cal.temp_85c_1v5 as i32);
+        if cal_85 <= cal_30 {
+            return Err(nb::Error::Other(AdcError::NoCalibration));
+        }
+
+        let count = self.read(&mut AdcTempSenseChannel).map_err(|_| nb::Error::WouldBlock)?;
+        // The calibration readings are 12-bit
+        let count = (count << (12 - self.resolution_bits())) as i32;
+        Ok((3000 + (count - cal_30) * 5500 / (cal_85 - cal_30)) as i16)
+    }
+
+    /// Convert channels `start_ch` to `end_ch` inclusive, storing the result of each channel in `out[channel - start_ch]`.
+    /// Results are unsigned, right-justified counts, as with `.adc_get_result()`.
+    /// 
+    /// The hardware sequence always runs from `end_ch` down to channel 0, so channels below `start_ch` are also 
+    /// converted but their results are discarded. Any conversion started by `.read()` is abandoned.
+    /// 
+    /// Returns an error if `start_ch > end_ch`, `end_ch > 15`, or `out` doesn't have exactly one slot per channel.
+    pub fn read_sequence(&mut self, start_ch: u8, end_ch: u8, out: &mut [u16]) -> Result<(), AdcError> {
+        if start_ch > end_ch || end_ch > 15 {
+            return Err(AdcError::InvalidChannels);
+        }
+        if out.len() != (end_ch - start_ch + 1) as usize {
+            return Err(AdcError::BufferLength);
+        }
+
+        self.start_sequence(end_ch);
+        for _ in 0..=end_ch {
+            self.start_conversion();
+            while self.adc_reg.adcifg.read().adcifg0().bit_is_clear() {}
+            // Reading the result clears the flag
+            let (ch, count) = self.adc_get_result_with_channel();
+            if ch >= start_ch {
+                out[(ch - start_ch) as usize] = count;
+            }
+        }
+        self.stop_sequence();
+        Ok(())
+    }
+
+    /// Put the ADC into sequence-of-channels mode starting from `end_ch`. Each conversion is started by `start_conversion()`.
+    fn start_sequence(&mut self, end_ch: u8) {
+        self.disable();
+        self.is_waiting = false;
+        self.adc_reg.adcmctl0.modify(|_, w| w.adcinch().bits(end_ch));
+        self.adc_reg.adcctl1.modify(|_, w| w.adcconseq().bits(0b01));
+        unsafe { self.adc_reg.adcifg.clear_bits(|w| w.adcifg0().clear_bit()) };
+        self.sequence = Some((end_ch, end_ch));
+        self.enable();
+    }
+
+    /// Return the ADC to single-channel mode.
+    fn stop_sequence(&mut self) {
+        self.disable();
+        self.sequence = None;
+        self.adc_reg.adcctl1.modify(|_, w| w.adcconseq().bits(0b00));
+        self.enable();
+    }
+
+    /// Start continuously sampling `pin` in the background, storing the results in `buffer` as a ring buffer.
+    /// Conversions follow one another as fast as the sample-and-hold time and resolution allow.
+    /// 
+    /// The conversion complete interrupt is enabled, and the ADC's ISR must call `AdcStream::on_interrupt()` to store each result.
+    /// If the main loop doesn't drain the buffer fast enough, the oldest samples are overwritten. 
+    /// Call `AdcStream::stop()` to stop sampling and get the ADC and buffer back.
+    pub fn start_streaming<PIN: Channel<Self, ID = u8>>(mut self, pin: &mut PIN, buffer: &'static mut [u16]) -> AdcStream {
+        self.disable();
+        self.is_waiting = false;
+        self.set_pin(pin);
+        // Repeat-single-channel mode, with each conversion triggering the next
+        self.adc_reg.adcctl1.modify(|_, w| w.adcconseq().bits(0b10));
+        unsafe {
+            self.adc_reg.adcctl0.set_bits(|w| w.adcmsc().set_bit());
+            self.adc_reg.adcifg.clear_bits(|w| w.adcifg0().clear_bit());
+            self.adc_reg.adcie.set_bits(|w| w.adcie0().set_bit());
+        }
+        self.enable();
+        self.start_conversion();
+
+        AdcStream { adc: self, buffer, head: 0, len: 0, overrun: false }
+    }
+}
+
+/// Background ADC sampling of a single channel, started by `Adc::start_streaming()`.
+pub struct AdcStream {
+    adc: Adc,
+    buffer: &'static mut [u16],
+    head: usize,
+    len: usize,
+    overrun: bool,
+}
+
+impl AdcStream {
+    /// Store the latest conversion result in the ring buffer. Call this from the ADC's ISR.
+    /// 
+    /// If the buffer is full the oldest sample is overwritten and `overrun()` will return true.
+    pub fn on_interrupt(&mut self) {
+        // Reading the result clears the interrupt flag
+        let sample = self.adc.adc_get_result();
+        let cap = self.buffer.len();
+        if cap == 0 {
+            return;
+        }
+        let tail = (self.head + self.len) % cap;
+        self.buffer[tail] = sample;
+        if self.len == cap {
+            self.head = (self.head + 1) % cap;
+            self.overrun = true;
+        } else {
+            self.len += 1;
+        }
+    }
+
+    /// Move the oldest buffered samples into `out`, returning how many were copied.
+    pub fn take_samples(&mut self, out: &mut [u16]) -> usize {
+        let count = self.len.min(out.len());
+        let cap = self.buffer.len();
+        for slot in out.iter_mut().take(count) {
+            *slot = self.buffer[self.head];
+            self.head = (self.head + 1) % cap;
+        }
+        self.len -= count;
+        count
+    }
+
+    /// Number of samples waiting in the buffer
+    pub fn available(&self) -> usize {
+        self.len
+    }
+
+    /// Whether any samples were overwritten before being taken. Clears the overrun flag.
+    pub fn overrun(&mut self) -> bool {
+        core::mem::replace(&mut self.overrun, false)
+    }
+
+    /// Stop sampling, returning the ADC to single conversion mode, and give back the ADC and buffer.
+    /// Samples still in the buffer are discarded.
+    pub fn stop(self) -> (Adc, &'static mut [u16]) {
+        let mut adc = self.adc;
+        adc.disable();
+        unsafe {
+            adc.adc_reg.adcie.clear_bits(|w| w.adcie0().clear_bit());
+            adc.adc_reg.adcctl0.clear_bits(|w| w.adcmsc().clear_bit());
+        }
+        adc.adc_reg.adcctl1.modify(|_, w| w.adcconseq().bits(0b00));
+        adc.enable();
+        (adc, self.buffer)
+    }
 }
 
 fn disable_adc_reg(adc: &mut ADC) {
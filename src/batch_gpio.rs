@@ -8,6 +8,14 @@
 //! For example, `P2.batch().config_pin3(|p| p.to_input_pullup()).config_pin1(|p| p.to_output()).split(&pmm)`
 //! configures P2.3 as a pullup input pin and P2.1 as an output pin and then writes the
 //! configuration to the hardware in a single set of writes.
+//!
+//! On interrupt-capable ports, input pins can also be armed with `PinProxy::interrupt_on()`, so the
+//! interrupt edge and enable are written in the same pass as the rest of the configuration.
+//!
+//! When waking from LPMx.5, the GPIO configuration must be restored before LOCKLPM5 is cleared by
+//! `Pmm::new()`. Save the configuration with `Batch::snapshot()` or `GpioSnapshot::capture()`
+//! before going to sleep, then call `GpioSnapshot::restore()` on wakeup before creating the `Pmm`.
+//! The same `Batch` configuration can then be split as usual to get the typed pins back.
 
 use crate::gpio::*;
 use crate::hw_traits::gpio::{GpioPeriph, IntrPeriph};
@@ -23,14 +31,28 @@ pub struct PinProxy<PORT: PortNum, PIN: PinNum, DIR> {
     _port: PhantomData<PORT>,
     _pin: PhantomData<PIN>,
     _dir: PhantomData<DIR>,
+    intr: Option<Edge>,
+}
+
+/// Input edge that triggers a pin interrupt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    /// Low-to-high transition
+    Rising,
+    /// High-to-low transition
+    Falling,
 }
 
 macro_rules! make_proxy {
     () => {
+        make_proxy!(None)
+    };
+    ($intr:expr) => {
         PinProxy {
             _port: PhantomData,
             _pin: PhantomData,
             _dir: PhantomData,
+            intr: $intr,
         }
     };
 }
@@ -39,28 +61,43 @@ impl<PORT: PortNum, PIN: PinNum, PULL> PinProxy<PORT, PIN, Input<PULL>> {
     /// Configures pin as pulldown input
     #[inline(always)]
     pub fn pulldown(self) -> PinProxy<PORT, PIN, Input<Pulldown>> {
-        make_proxy!()
+        make_proxy!(self.intr)
     }
 
     /// Configures pin as pullup input
     #[inline(always)]
     pub fn pullup(self) -> PinProxy<PORT, PIN, Input<Pullup>> {
-        make_proxy!()
+        make_proxy!(self.intr)
     }
 
     /// Configures pin as floating input
     #[inline(always)]
     pub fn floating(self) -> PinProxy<PORT, PIN, Input<Floating>> {
-        make_proxy!()
+        make_proxy!(self.intr)
     }
 
-    /// Configures pin as output
+    /// Configures pin as output. Any interrupt configuration is dropped.
     #[inline(always)]
     pub fn to_output(self) -> PinProxy<PORT, PIN, Output> {
         make_proxy!()
     }
 }
 
+impl<PORT: IntrPortNum, PIN: PinNum, PULL> PinProxy<PORT, PIN, Input<PULL>> {
+    /// Enable the pin's interrupt on the given edge when the batch is committed. The interrupt
+    /// flag is cleared beforehand, so only edges after the split trigger the interrupt.
+    #[inline(always)]
+    pub fn interrupt_on(self, edge: Edge) -> Self {
+        make_proxy!(Some(edge))
+    }
+
+    /// Leave the pin's interrupt disabled when the batch is committed
+    #[inline(always)]
+    pub fn no_interrupt(self) -> Self {
+        make_proxy!()
+    }
+}
+
 impl<PORT: PortNum, PIN: PinNum> PinProxy<PORT, PIN, Output> {
     /// Configures pin as floating input
     #[inline(always)]
@@ -201,6 +238,8 @@ trait MaskRegisters {
     fn pxren_mask(&self) -> u8;
     fn pxsel0_mask(&self) -> u8;
     fn pxsel1_mask(&self) -> u8;
+    fn pxies_mask(&self) -> u8;
+    fn pxie_mask(&self) -> u8;
 }
 
 impl<PORT: PortNum, PIN: PinNum, DIR> MaskRegisters for PinProxy<PORT, PIN, DIR> {
@@ -228,21 +267,135 @@ impl<PORT: PortNum, PIN: PinNum, DIR> MaskRegisters for PinProxy<PORT, PIN, DIR>
     fn pxsel1_mask(&self) -> u8 {
         (self.pxsel1_on() as u8) << PIN::NUM
     }
+
+    #[inline(always)]
+    fn pxies_mask(&self) -> u8 {
+        ((self.intr == Some(Edge::Falling)) as u8) << PIN::NUM
+    }
+
+    #[inline(always)]
+    fn pxie_mask(&self) -> u8 {
+        (self.intr.is_some() as u8) << PIN::NUM
+    }
 }
 
 trait InterruptOperations {
+    fn maybe_pxies_rd(&self) -> u8;
+    fn maybe_pxie_rd(&self) -> u8;
+    fn maybe_set_pxies(&self, b: u8);
     fn maybe_set_pxie(&self, b: u8);
+    fn maybe_clear_pxifg(&self, mask: u8);
 }
 
 impl<P: GpioPeriph> InterruptOperations for P {
+    #[inline(always)]
+    default fn maybe_pxies_rd(&self) -> u8 {
+        0
+    }
+
+    #[inline(always)]
+    default fn maybe_pxie_rd(&self) -> u8 {
+        0
+    }
+
+    #[inline(always)]
+    default fn maybe_set_pxies(&self, _b: u8) {}
+
     #[inline(always)]
     default fn maybe_set_pxie(&self, _b: u8) {}
+
+    #[inline(always)]
+    default fn maybe_clear_pxifg(&self, _mask: u8) {}
 }
 
 impl<P: IntrPeriph> InterruptOperations for P {
+    #[inline(always)]
+    fn maybe_pxies_rd(&self) -> u8 {
+        self.pxies_rd()
+    }
+
+    #[inline(always)]
+    fn maybe_pxie_rd(&self) -> u8 {
+        self.pxie_rd()
+    }
+
+    #[inline(always)]
+    fn maybe_set_pxies(&self, b: u8) {
+        self.pxies_wr(b);
+    }
+
     #[inline(always)]
     fn maybe_set_pxie(&self, b: u8) {
-        self.pxie_set(b);
+        self.pxie_wr(b);
+    }
+
+    #[inline(always)]
+    fn maybe_clear_pxifg(&self, mask: u8) {
+        self.pxifg_clear(!mask);
+    }
+}
+
+/// Saved register values of a GPIO port's configuration, for restoring the exact same
+/// configuration after waking from LPMx.5. The fields can be persisted in backup memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GpioSnapshot {
+    /// PxOUT value
+    pub pxout: u8,
+    /// PxDIR value
+    pub pxdir: u8,
+    /// PxREN value
+    pub pxren: u8,
+    /// PxSEL0 value
+    pub pxsel0: u8,
+    /// PxSEL1 value
+    pub pxsel1: u8,
+    /// PxIES value. Always 0 on ports without interrupts.
+    pub pxies: u8,
+    /// PxIE value. Always 0 on ports without interrupts.
+    pub pxie: u8,
+}
+
+impl GpioSnapshot {
+    /// Capture the current configuration of a GPIO port from its registers
+    #[inline]
+    pub fn capture<PORT: PortNum>(_port: &PORT) -> Self {
+        let p = unsafe { PORT::steal() };
+        GpioSnapshot {
+            pxout: p.pxout_rd(),
+            pxdir: p.pxdir_rd(),
+            pxren: p.pxren_rd(),
+            pxsel0: p.pxsel0_rd(),
+            pxsel1: p.pxsel1_rd(),
+            pxies: p.maybe_pxies_rd(),
+            pxie: p.maybe_pxie_rd(),
+        }
+    }
+
+    /// Write the saved configuration back to the GPIO port. Unlike `Batch::split()`, this doesn't
+    /// require a `Pmm`, so it can be called before LOCKLPM5 is cleared when waking from LPMx.5.
+    /// Pin interrupts are re-enabled last, and pending interrupt flags are left alone since they
+    /// record which pin woke the device.
+    #[inline]
+    pub fn restore<PORT: PortNum>(&self, _port: &PORT) {
+        self.write_regs(&unsafe { PORT::steal() }, false);
+    }
+
+    #[inline]
+    fn write_regs<PORT: PortNum>(&self, p: &PORT, clear_intr_flags: bool) {
+        // Turn off interrupts first so nothing fires during subsequent register writes
+        p.maybe_set_pxie(0);
+        // Select the edges before the pins are configured, so they never see the wrong edge
+        p.maybe_set_pxies(self.pxies);
+        p.pxsel0_wr(self.pxsel0);
+        p.pxsel1_wr(self.pxsel1);
+        p.pxout_wr(self.pxout);
+        p.pxdir_wr(self.pxdir);
+        p.pxren_wr(self.pxren);
+        if clear_intr_flags {
+            // Changing PxIES can set PxIFG, so clear the flags of the enabled pins
+            p.maybe_clear_pxifg(self.pxie);
+        }
+        p.maybe_set_pxie(self.pxie);
     }
 }
 
@@ -281,8 +434,10 @@ pub struct Batch<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
 impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
     Batch<PORT, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
 {
+    /// Register values that this batch configuration writes to the port when split. Compare it
+    /// against a saved snapshot to check that the configuration matches.
     #[inline]
-    fn write_regs(&self) {
+    pub fn snapshot(&self) -> GpioSnapshot {
         let pxdir = 0u8
             .set_mask(self.pin0.pxdir_mask())
             .set_mask(self.pin1.pxdir_mask())
@@ -333,14 +488,41 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
             .set_mask(self.pin6.pxsel1_mask())
             .set_mask(self.pin7.pxsel1_mask());
 
+        let pxies = 0u8
+            .set_mask(self.pin0.pxies_mask())
+            .set_mask(self.pin1.pxies_mask())
+            .set_mask(self.pin2.pxies_mask())
+            .set_mask(self.pin3.pxies_mask())
+            .set_mask(self.pin4.pxies_mask())
+            .set_mask(self.pin5.pxies_mask())
+            .set_mask(self.pin6.pxies_mask())
+            .set_mask(self.pin7.pxies_mask());
+
+        let pxie = 0u8
+            .set_mask(self.pin0.pxie_mask())
+            .set_mask(self.pin1.pxie_mask())
+            .set_mask(self.pin2.pxie_mask())
+            .set_mask(self.pin3.pxie_mask())
+            .set_mask(self.pin4.pxie_mask())
+            .set_mask(self.pin5.pxie_mask())
+            .set_mask(self.pin6.pxie_mask())
+            .set_mask(self.pin7.pxie_mask());
+
+        GpioSnapshot {
+            pxout,
+            pxdir,
+            pxren,
+            pxsel0,
+            pxsel1,
+            pxies,
+            pxie,
+        }
+    }
+
+    #[inline]
+    fn write_regs(&self) {
         let p = unsafe { PORT::steal() };
-        // Turn off interrupts first so nothing fires during subsequent register writes
-        p.maybe_set_pxie(0);
-        p.pxsel0_wr(pxsel0);
-        p.pxsel1_wr(pxsel1);
-        p.pxout_wr(pxout);
-        p.pxdir_wr(pxdir);
-        p.pxren_wr(pxren);
+        self.snapshot().write_regs(&p, true);
     }
 
     #[inline(always)]
@@ -357,11 +539,12 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
         }
     }
 
-    /// Commits all pin configurations to GPIO registers and returns GPIO parts and turns off all
-    /// interrupt enable bits.
+    /// Commits all pin configurations to GPIO registers and returns GPIO parts. Interrupts are
+    /// disabled while the registers are written, then enabled only for the pins configured with
+    /// `PinProxy::interrupt_on()`, so always-armed pins are never left unarmed after the split.
     ///
-    /// Note that the pin's interrupt flags may become set as a result of
-    /// this operation.
+    /// Note that the interrupt flags of the other pins may become set as a result of this
+    /// operation.
     ///
     /// GPIO input/output operations only work after the LOCKLPM5 bit has been set, which is
     /// ensured when passing `&Pmm` into the method, since a `Pmm` is created only by setting
@@ -380,13 +563,13 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
     ) -> Batch<PORT, NEW, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7> {
         Batch {
             pin0: f(self.pin0),
-            pin1: make_proxy!(),
-            pin2: make_proxy!(),
-            pin3: make_proxy!(),
-            pin4: make_proxy!(),
-            pin5: make_proxy!(),
-            pin6: make_proxy!(),
-            pin7: make_proxy!(),
+            pin1: self.pin1,
+            pin2: self.pin2,
+            pin3: self.pin3,
+            pin4: self.pin4,
+            pin5: self.pin5,
+            pin6: self.pin6,
+            pin7: self.pin7,
         }
     }
 
@@ -397,14 +580,14 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
         f: F,
     ) -> Batch<PORT, DIR0, NEW, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7> {
         Batch {
-            pin0: make_proxy!(),
+            pin0: self.pin0,
             pin1: f(self.pin1),
-            pin2: make_proxy!(),
-            pin3: make_proxy!(),
-            pin4: make_proxy!(),
-            pin5: make_proxy!(),
-            pin6: make_proxy!(),
-            pin7: make_proxy!(),
+            pin2: self.pin2,
+            pin3: self.pin3,
+            pin4: self.pin4,
+            pin5: self.pin5,
+            pin6: self.pin6,
+            pin7: self.pin7,
         }
     }
 
@@ -415,14 +598,14 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
         f: F,
     ) -> Batch<PORT, DIR0, DIR1, NEW, DIR3, DIR4, DIR5, DIR6, DIR7> {
         Batch {
-            pin0: make_proxy!(),
-            pin1: make_proxy!(),
+            pin0: self.pin0,
+            pin1: self.pin1,
             pin2: f(self.pin2),
-            pin3: make_proxy!(),
-            pin4: make_proxy!(),
-            pin5: make_proxy!(),
-            pin6: make_proxy!(),
-            pin7: make_proxy!(),
+            pin3: self.pin3,
+            pin4: self.pin4,
+            pin5: self.pin5,
+            pin6: self.pin6,
+            pin7: self.pin7,
         }
     }
 
@@ -433,14 +616,14 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
         f: F,
     ) -> Batch<PORT, DIR0, DIR1, DIR2, NEW, DIR4, DIR5, DIR6, DIR7> {
         Batch {
-            pin0: make_proxy!(),
-            pin1: make_proxy!(),
-            pin2: make_proxy!(),
+            pin0: self.pin0,
+            pin1: self.pin1,
+            pin2: self.pin2,
             pin3: f(self.pin3),
-            pin4: make_proxy!(),
-            pin5: make_proxy!(),
-            pin6: make_proxy!(),
-            pin7: make_proxy!(),
+            pin4: self.pin4,
+            pin5: self.pin5,
+            pin6: self.pin6,
+            pin7: self.pin7,
         }
     }
 
@@ -451,14 +634,14 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
         f: F,
     ) -> Batch<PORT, DIR0, DIR1, DIR2, DIR3, NEW, DIR5, DIR6, DIR7> {
         Batch {
-            pin0: make_proxy!(),
-            pin1: make_proxy!(),
-            pin2: make_proxy!(),
-            pin3: make_proxy!(),
+            pin0: self.pin0,
+            pin1: self.pin1,
+            pin2: self.pin2,
+            pin3: self.pin3,
             pin4: f(self.pin4),
-            pin5: make_proxy!(),
-            pin6: make_proxy!(),
-            pin7: make_proxy!(),
+            pin5: self.pin5,
+            pin6: self.pin6,
+            pin7: self.pin7,
         }
     }
 
@@ -469,14 +652,14 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
         f: F,
     ) -> Batch<PORT, DIR0, DIR1, DIR2, DIR3, DIR4, NEW, DIR6, DIR7> {
         Batch {
-            pin0: make_proxy!(),
-            pin1: make_proxy!(),
-            pin2: make_proxy!(),
-            pin3: make_proxy!(),
-            pin4: make_proxy!(),
+            pin0: self.pin0,
+            pin1: self.pin1,
+            pin2: self.pin2,
+            pin3: self.pin3,
+            pin4: self.pin4,
             pin5: f(self.pin5),
-            pin6: make_proxy!(),
-            pin7: make_proxy!(),
+            pin6: self.pin6,
+            pin7: self.pin7,
         }
     }
 
@@ -487,14 +670,14 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
         f: F,
     ) -> Batch<PORT, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, NEW, DIR7> {
         Batch {
-            pin0: make_proxy!(),
-            pin1: make_proxy!(),
-            pin2: make_proxy!(),
-            pin3: make_proxy!(),
-            pin4: make_proxy!(),
-            pin5: make_proxy!(),
+            pin0: self.pin0,
+            pin1: self.pin1,
+            pin2: self.pin2,
+            pin3: self.pin3,
+            pin4: self.pin4,
+            pin5: self.pin5,
             pin6: f(self.pin6),
-            pin7: make_proxy!(),
+            pin7: self.pin7,
         }
     }
 
@@ -505,13 +688,13 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
         f: F,
     ) -> Batch<PORT, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, NEW> {
         Batch {
-            pin0: make_proxy!(),
-            pin1: make_proxy!(),
-            pin2: make_proxy!(),
-            pin3: make_proxy!(),
-            pin4: make_proxy!(),
-            pin5: make_proxy!(),
-            pin6: make_proxy!(),
+            pin0: self.pin0,
+            pin1: self.pin1,
+            pin2: self.pin2,
+            pin3: self.pin3,
+            pin4: self.pin4,
+            pin5: self.pin5,
+            pin6: self.pin6,
             pin7: f(self.pin7),
         }
     }
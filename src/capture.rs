@@ -45,6 +45,7 @@ impl From<CapTrigger> for Cm {
 struct PinConfig {
     select: Ccis,
     trigger: CapTrigger,
+    sync: bool,
 }
 
 impl Default for PinConfig {
@@ -52,6 +53,7 @@ impl Default for PinConfig {
         Self {
             select: Ccis::Gnd,
             trigger: CapTrigger::RisingEdge,
+            sync: true,
         }
     }
 }
@@ -108,7 +110,7 @@ impl CapturePeriph for pac::TB3 {
 }
 
 macro_rules! config_fn {
-    (methods $config_sel_b:ident, $config_trigger:ident, $pin:ident) => {
+    (methods $config_sel_b:ident, $config_trigger:ident, $config_sync:ident, $pin:ident) => {
         #[allow(non_snake_case)]
         #[inline(always)]
         /// Configure the capture input select of the capture pin as capture input B
@@ -123,9 +125,18 @@ macro_rules! config_fn {
             self.$pin.trigger = trigger;
             self
         }
+
+        #[inline(always)]
+        /// Configure whether the capture input is synchronized to the timer clock. Synchronous
+        /// capture is enabled by default and avoids race conditions between the input edge and
+        /// the timer clock, at the cost of up to one timer clock of latency.
+        pub fn $config_sync(mut self, sync: bool) -> Self {
+            self.$pin.sync = sync;
+            self
+        }
     };
 
-    ($config_sel_a:ident, $config_sel_b:ident, $config_trigger:ident, $pin:ident, $gpio:ident) => {
+    ($config_sel_a:ident, $config_sel_b:ident, $config_trigger:ident, $config_sync:ident, $pin:ident, $gpio:ident) => {
         #[allow(non_snake_case)]
         #[inline(always)]
         /// Configure the capture input select of the capture pin as capture input A, which
@@ -134,10 +145,10 @@ macro_rules! config_fn {
             self.$pin.select = Ccis::InputA;
             self
         }
-        config_fn!(methods $config_sel_b, $config_trigger, $pin);
+        config_fn!(methods $config_sel_b, $config_trigger, $config_sync, $pin);
     };
 
-    ($config_sel_a:ident, $config_sel_b:ident, $config_trigger:ident, $pin:ident) => {
+    ($config_sel_a:ident, $config_sel_b:ident, $config_trigger:ident, $config_sync:ident, $pin:ident) => {
         #[allow(non_snake_case)]
         #[inline(always)]
         /// Configure the capture input select of the capture pin as capture input A
@@ -145,7 +156,7 @@ macro_rules! config_fn {
             self.$pin.select = Ccis::InputA;
             self
         }
-        config_fn!(methods $config_sel_b, $config_trigger, $pin);
+        config_fn!(methods $config_sel_b, $config_trigger, $config_sync, $pin);
     };
 }
 
@@ -154,8 +165,8 @@ macro_rules! config_fn {
 ///
 /// Each pin has a input source, which determines the signal that controls the capture, and a
 /// capture trigger event, which determines the input transitions that actually trigger the
-/// capture. By default, all pins use GND as their input source and trigger a capture on a rising
-/// edge.
+/// capture. By default, all pins use GND as their input source, trigger a capture on a rising
+/// edge, and synchronize the capture input to the timer clock.
 pub struct CaptureConfig3<T: CapturePeriph>
 where
     T: CapCmpTimer3,
@@ -185,12 +196,14 @@ impl<T: CapturePeriph + CapCmpTimer3> CaptureConfig3<T> {
         config_cap0_input_A,
         config_cap0_input_B,
         config_cap0_trigger,
+        config_cap0_sync,
         cap0
     );
     config_fn!(
         config_cap1_input_A,
         config_cap1_input_B,
         config_cap1_trigger,
+        config_cap1_sync,
         cap1,
         Gpio1
     );
@@ -198,6 +211,7 @@ impl<T: CapturePeriph + CapCmpTimer3> CaptureConfig3<T> {
         config_cap2_input_A,
         config_cap2_input_B,
         config_cap2_trigger,
+        config_cap2_sync,
         cap2,
         Gpio2
     );
@@ -206,9 +220,24 @@ impl<T: CapturePeriph + CapCmpTimer3> CaptureConfig3<T> {
     pub fn commit(self) -> CaptureParts3<T> {
         let timer = self.timer;
         self.config.write_regs(&timer);
-        CCRn::<CCR0>::config_cap_mode(&timer, self.cap0.trigger.into(), self.cap0.select);
-        CCRn::<CCR1>::config_cap_mode(&timer, self.cap1.trigger.into(), self.cap1.select);
-        CCRn::<CCR2>::config_cap_mode(&timer, self.cap2.trigger.into(), self.cap2.select);
+        CCRn::<CCR0>::config_cap_mode(
+            &timer,
+            self.cap0.trigger.into(),
+            self.cap0.select,
+            self.cap0.sync,
+        );
+        CCRn::<CCR1>::config_cap_mode(
+            &timer,
+            self.cap1.trigger.into(),
+            self.cap1.select,
+            self.cap1.sync,
+        );
+        CCRn::<CCR2>::config_cap_mode(
+            &timer,
+            self.cap2.trigger.into(),
+            self.cap2.select,
+            self.cap2.sync,
+        );
         timer.continuous();
 
         CaptureParts3 {
@@ -225,8 +254,8 @@ impl<T: CapturePeriph + CapCmpTimer3> CaptureConfig3<T> {
 ///
 /// Each pin has a input source, which determines the signal that controls the capture, and a
 /// capture trigger event, which determines the input transitions that actually trigger the
-/// capture. By default, all pins use GND as their input source and trigger a capture on a rising
-/// edge.
+/// capture. By default, all pins use GND as their input source, trigger a capture on a rising
+/// edge, and synchronize the capture input to the timer clock.
 pub struct CaptureConfig7<T: CapturePeriph>
 where
     T: CapCmpTimer7,
@@ -264,12 +293,14 @@ impl<T: CapturePeriph + CapCmpTimer7> CaptureConfig7<T> {
         config_cap0_input_A,
         config_cap0_input_B,
         config_cap0_trigger,
+        config_cap0_sync,
         cap0
     );
     config_fn!(
         config_cap1_input_A,
         config_cap1_input_B,
         config_cap1_trigger,
+        config_cap1_sync,
         cap1,
         Gpio1
     );
@@ -277,6 +308,7 @@ impl<T: CapturePeriph + CapCmpTimer7> CaptureConfig7<T> {
         config_cap2_input_A,
         config_cap2_input_B,
         config_cap2_trigger,
+        config_cap2_sync,
         cap2,
         Gpio2
     );
@@ -284,6 +316,7 @@ impl<T: CapturePeriph + CapCmpTimer7> CaptureConfig7<T> {
         config_cap3_input_A,
         config_cap3_input_B,
         config_cap3_trigger,
+        config_cap3_sync,
         cap3,
         Gpio3
     );
@@ -291,6 +324,7 @@ impl<T: CapturePeriph + CapCmpTimer7> CaptureConfig7<T> {
         config_cap4_input_A,
         config_cap4_input_B,
         config_cap4_trigger,
+        config_cap4_sync,
         cap4,
         Gpio4
     );
@@ -298,6 +332,7 @@ impl<T: CapturePeriph + CapCmpTimer7> CaptureConfig7<T> {
         config_cap5_input_A,
         config_cap5_input_B,
         config_cap5_trigger,
+        config_cap5_sync,
         cap5,
         Gpio5
     );
@@ -305,6 +340,7 @@ impl<T: CapturePeriph + CapCmpTimer7> CaptureConfig7<T> {
         config_cap6_input_A,
         config_cap6_input_B,
         config_cap6_trigger,
+        config_cap6_sync,
         cap6,
         Gpio6
     );
@@ -313,13 +349,48 @@ impl<T: CapturePeriph + CapCmpTimer7> CaptureConfig7<T> {
     pub fn commit(self) -> CaptureParts7<T> {
         let timer = self.timer;
         self.config.write_regs(&timer);
-        CCRn::<CCR0>::config_cap_mode(&timer, self.cap0.trigger.into(), self.cap0.select);
-        CCRn::<CCR1>::config_cap_mode(&timer, self.cap1.trigger.into(), self.cap1.select);
-        CCRn::<CCR2>::config_cap_mode(&timer, self.cap2.trigger.into(), self.cap2.select);
-        CCRn::<CCR3>::config_cap_mode(&timer, self.cap3.trigger.into(), self.cap3.select);
-        CCRn::<CCR4>::config_cap_mode(&timer, self.cap4.trigger.into(), self.cap4.select);
-        CCRn::<CCR5>::config_cap_mode(&timer, self.cap5.trigger.into(), self.cap5.select);
-        CCRn::<CCR6>::config_cap_mode(&timer, self.cap6.trigger.into(), self.cap6.select);
+        CCRn::<CCR0>::config_cap_mode(
+            &timer,
+            self.cap0.trigger.into(),
+            self.cap0.select,
+            self.cap0.sync,
+        );
+        CCRn::<CCR1>::config_cap_mode(
+            &timer,
+            self.cap1.trigger.into(),
+            self.cap1.select,
+            self.cap1.sync,
+        );
+        CCRn::<CCR2>::config_cap_mode(
+            &timer,
+            self.cap2.trigger.into(),
+            self.cap2.select,
+            self.cap2.sync,
+        );
+        CCRn::<CCR3>::config_cap_mode(
+            &timer,
+            self.cap3.trigger.into(),
+            self.cap3.select,
+            self.cap3.sync,
+        );
+        CCRn::<CCR4>::config_cap_mode(
+            &timer,
+            self.cap4.trigger.into(),
+            self.cap4.select,
+            self.cap4.sync,
+        );
+        CCRn::<CCR5>::config_cap_mode(
+            &timer,
+            self.cap5.trigger.into(),
+            self.cap5.select,
+            self.cap5.sync,
+        );
+        CCRn::<CCR6>::config_cap_mode(
+            &timer,
+            self.cap6.trigger.into(),
+            self.cap6.select,
+            self.cap6.sync,
+        );
         timer.continuous();
 
         CaptureParts7 {
@@ -371,7 +442,7 @@ pub struct CaptureParts7<T: CapCmpTimer7> {
 pub struct Capture<T: CapCmp<C>, C>(PhantomData<T>, PhantomData<C>);
 
 impl<T: CapCmp<C>, C> Capture<T, C> {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self(PhantomData, PhantomData)
     }
 }
@@ -430,6 +501,16 @@ impl<T: CapCmp<C>, C> Capture<T, C> {
         let timer = unsafe { T::steal() };
         timer.ccie_clr();
     }
+
+    #[inline]
+    /// Read the most recent capture value along with whether an overcapture has occurred, without
+    /// waiting for a new capture and without clearing any flags. Useful for polling loops that
+    /// only care about the latest edge and treat missed edges as informational.
+    pub fn last_capture(&self) -> (u16, bool) {
+        let timer = unsafe { T::steal() };
+        let (cov, _) = timer.cov_ccifg_rd();
+        (timer.get_ccrn(), cov)
+    }
 }
 
 /// Error returned when the previous capture was overwritten before being read
@@ -1,17 +1,43 @@
 //! Clock system for configuration of MCLK, SMCLK, and ACLK.
 //!
 //! Once configuration is complete, `Aclk` and `Smclk` clock objects are returned. The clock
-//! objects are used to set the clock sources on other peripherals.
+//! objects are used to set the clock sources on other peripherals. `freeze_all()` additionally
+//! returns an `Mclk` object for code that needs the CPU clock frequency.
 //! Configuration of MCLK and SMCLK *must* occur, though SMCLK can be disabled. In that case, only
 //! `Aclk` is returned.
 //!
-//! DCO with FLL is supported on MCLK for select frequencies. Supporting arbitrary frequencies on
-//! the DCO requires complex calibration routines not supported by the HAL.
+//! DCO with FLL is supported on MCLK for select frequencies using the factory trim. Other
+//! frequencies between 1 and 24 MHz can be selected with `mclk_dcoclk_custom()`, which runs the
+//! software DCO trim routine from the user's guide to centre the DCO in its range. The resulting
+//! frequency is the nearest multiple of REFOCLK to the requested one.
+//!
+//! SMCLK has no clock source selection of its own. In hardware it is always derived from the
+//! *divided* MCLK output, so its frequency is `MCLK / smclk_div`. This means SMCLK can never run
+//! faster than MCLK: if a peripheral such as SPI needs a fast SMCLK, MCLK must be at least as fast.
+//! Always use `Smclk::freq()` when computing baud rates or other timing parameters, since it
+//! accounts for both dividers.
+//!
+//! Neither MCLK nor SMCLK may exceed `MAX_FREQ`. Every frequency selectable through
+//! `DcoclkFreqSel` is within this limit, so no combination of clock settings can overclock the
+//! device, but debug builds still check the resulting frequencies and FRAM wait states.
+//!
+//! A low-frequency watch crystal on XIN/XOUT can be used as the ACLK or MCLK source by creating
+//! an `Lfxt` from the crystal pins. The crystal is started when the clocks are frozen, which fails
+//! with `ClockError::Xt1Fault` if the oscillator never stabilizes.
+//!
+//! Freezing the clocks fails with `ClockError::FllUnlock` if the FLL can't lock onto the DCO
+//! frequency within a bounded time, rather than hanging. A software-trimmed DCO falls back to the
+//! factory trim of its range before giving up. The clocks are left running unlocked on failure,
+//! so the caller can decide whether to carry on, retry or reset.
+//!
+//! For dynamic frequency scaling, `freeze_scalable()` additionally returns a `DcoScaler`, which
+//! can switch MCLK between DCO frequencies at runtime.
 
 use core::arch::asm;
 
 use crate::delay::Delay;
-use crate::fram::{Fram, WaitStates};
+use crate::fram::Fram;
+use crate::gpio::{Alternate2, Floating, Input, Pin, Pin6, Pin7, P2};
 use msp430fr2355 as pac;
 use pac::cs::csctl1::DCORSEL_A;
 use pac::cs::csctl4::{SELA_A, SELMS_A};
@@ -26,6 +52,10 @@ enum MclkSel {
     Refoclk,
     Vloclk,
     Dcoclk(DcoclkFreqSel),
+    // FLL multiplier of a software-trimmed DCO frequency
+    DcoclkCustom(u16),
+    // Crystal frequency
+    Lfxt(u16),
 }
 
 impl MclkSel {
@@ -35,6 +65,8 @@ impl MclkSel {
             MclkSel::Vloclk => VLOCLK as u32,
             MclkSel::Refoclk => REFOCLK as u32,
             MclkSel::Dcoclk(sel) => sel.freq(),
+            MclkSel::DcoclkCustom(multiplier) => *multiplier as u32 * REFOCLK as u32,
+            MclkSel::Lfxt(freq) => *freq as u32,
         }
     }
 
@@ -43,7 +75,8 @@ impl MclkSel {
         match self {
             MclkSel::Vloclk => SELMS_A::VLOCLK,
             MclkSel::Refoclk => SELMS_A::REFOCLK,
-            MclkSel::Dcoclk(_) => SELMS_A::DCOCLKDIV,
+            MclkSel::Dcoclk(_) | MclkSel::DcoclkCustom(_) => SELMS_A::DCOCLKDIV,
+            MclkSel::Lfxt(_) => SELMS_A::XT1CLK,
         }
     }
 }
@@ -52,6 +85,7 @@ impl MclkSel {
 enum AclkSel {
     Vloclk,
     Refoclk,
+    Lfxt(u16),
 }
 
 impl AclkSel {
@@ -60,6 +94,7 @@ impl AclkSel {
         match self {
             AclkSel::Vloclk => SELA_A::VLOCLK,
             AclkSel::Refoclk => SELA_A::REFOCLK,
+            AclkSel::Lfxt(_) => SELA_A::XT1CLK,
         }
     }
 
@@ -68,10 +103,32 @@ impl AclkSel {
         match self {
             AclkSel::Vloclk => VLOCLK,
             AclkSel::Refoclk => REFOCLK,
+            AclkSel::Lfxt(freq) => freq,
         }
     }
 }
 
+/// Low-frequency crystal oscillator (LFXT) connected to the XIN (P2.7) and XOUT (P2.6) pins,
+/// usually a 32768 Hz watch crystal. Pass it to `ClockConfig::aclk_lfxt()` or
+/// `ClockConfig::mclk_lfxt()` to use the crystal as a clock source.
+#[derive(Clone, Copy)]
+pub struct Lfxt {
+    freq: u16,
+}
+
+impl Lfxt {
+    /// Claim the crystal pins. `freq` is the crystal frequency in Hz, which is reported by the
+    /// clock objects of any clock sourced from the crystal.
+    #[inline]
+    pub fn new(
+        _xin: Pin<P2, Pin7, Alternate2<Input<Floating>>>,
+        _xout: Pin<P2, Pin6, Alternate2<Input<Floating>>>,
+        freq: u16,
+    ) -> Self {
+        Lfxt { freq }
+    }
+}
+
 /// Selectable DCOCLK frequencies when using factory trim settings.
 /// Actual frequencies may be slightly higher.
 #[derive(Clone, Copy)]
@@ -130,6 +187,32 @@ impl DcoclkFreqSel {
     }
 }
 
+// DCO range whose nominal frequency is closest to `freq`
+#[inline]
+fn dcorsel_for(freq: u32) -> DCORSEL_A {
+    match freq {
+        0..=1_500_000 => DCORSEL_A::DCORSEL_0,
+        1_500_001..=3_000_000 => DCORSEL_A::DCORSEL_1,
+        3_000_001..=6_000_000 => DCORSEL_A::DCORSEL_2,
+        6_000_001..=10_000_000 => DCORSEL_A::DCORSEL_3,
+        10_000_001..=14_000_000 => DCORSEL_A::DCORSEL_4,
+        14_000_001..=18_000_000 => DCORSEL_A::DCORSEL_5,
+        18_000_001..=22_000_000 => DCORSEL_A::DCORSEL_6,
+        _ => DCORSEL_A::DCORSEL_7,
+    }
+}
+
+/// Errors from configuring the clock system
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockError {
+    /// The requested DCO frequency is outside the supported range of 1 to 24 MHz
+    FreqOutOfRange,
+    /// The FLL didn't lock onto the DCO frequency in time
+    FllUnlock,
+    /// The crystal oscillator kept reporting a fault, so the crystal never started
+    Xt1Fault,
+}
+
 /// Typestate for `ClockConfig` that represents unconfigured clocks
 pub struct NoClockDefined;
 /// Typestate for `ClockConfig` that represents a configured MCLK
@@ -212,7 +295,24 @@ impl<MCLK, SMCLK> ClockConfig<MCLK, SMCLK> {
         self
     }
 
-    /// Select REFOCLK for MCLK and set the MCLK divider. Frequency is `10000 / mclk_div` Hz.
+    /// Select the LFXT crystal for ACLK
+    #[inline]
+    pub fn aclk_lfxt(mut self, lfxt: &Lfxt) -> Self {
+        self.aclk_sel = AclkSel::Lfxt(lfxt.freq);
+        self
+    }
+
+    /// Select the LFXT crystal for MCLK and set the MCLK divider. Frequency is
+    /// `crystal frequency / mclk_div` Hz.
+    #[inline]
+    pub fn mclk_lfxt(self, lfxt: &Lfxt, mclk_div: MclkDiv) -> ClockConfig<MclkDefined, SMCLK> {
+        ClockConfig {
+            mclk_div,
+            ..make_clkconf!(self, MclkDefined(MclkSel::Lfxt(lfxt.freq)), self.smclk)
+        }
+    }
+
+    /// Select REFOCLK for MCLK and set the MCLK divider. Frequency is `32768 / mclk_div` Hz.
     #[inline]
     pub fn mclk_refoclk(self, mclk_div: MclkDiv) -> ClockConfig<MclkDefined, SMCLK> {
         ClockConfig {
@@ -221,7 +321,7 @@ impl<MCLK, SMCLK> ClockConfig<MCLK, SMCLK> {
         }
     }
 
-    /// Select VLOCLK for MCLK and set the MCLK divider. Frequency is `32768 / mclk_div` Hz.
+    /// Select VLOCLK for MCLK and set the MCLK divider. Frequency is `10000 / mclk_div` Hz.
     #[inline]
     pub fn mclk_vcoclk(self, mclk_div: MclkDiv) -> ClockConfig<MclkDefined, SMCLK> {
         ClockConfig {
@@ -245,7 +345,34 @@ impl<MCLK, SMCLK> ClockConfig<MCLK, SMCLK> {
         }
     }
 
-    /// Enable SMCLK and set SMCLK divider, which divides the MCLK frequency
+    /// Select DCOCLK for MCLK at an arbitrary frequency between 1 and 24 MHz, trimmed in software
+    /// and stabilized with the FLL. The DCO frequency is rounded to the nearest multiple of
+    /// REFOCLK, so the MCLK frequency is `round(freq_hz / 32768) * 32768 / mclk_div` Hz.
+    ///
+    /// Returns an error if `freq_hz` is out of range.
+    #[inline]
+    pub fn mclk_dcoclk_custom(
+        self,
+        freq_hz: u32,
+        mclk_div: MclkDiv,
+    ) -> Result<ClockConfig<MclkDefined, SMCLK>, ClockError> {
+        if !(1_000_000..=MAX_FREQ).contains(&freq_hz) {
+            return Err(ClockError::FreqOutOfRange);
+        }
+        let multiplier = ((freq_hz + REFOCLK as u32 / 2) / REFOCLK as u32) as u16;
+        Ok(ClockConfig {
+            mclk_div,
+            ..make_clkconf!(
+                self,
+                MclkDefined(MclkSel::DcoclkCustom(multiplier)),
+                self.smclk
+            )
+        })
+    }
+
+    /// Enable SMCLK and set SMCLK divider, which divides the MCLK frequency. SMCLK shares its
+    /// source with MCLK and is divided from the MCLK output, so the resulting frequency is
+    /// `MCLK / div`, where MCLK already includes the MCLK divider.
     #[inline]
     pub fn smclk_on(self, div: SmclkDiv) -> ClockConfig<MCLK, SmclkDefined> {
         make_clkconf!(self, self.mclk, SmclkDefined(div))
@@ -270,40 +397,197 @@ fn fll_on() {
     unsafe { asm!("bic.b 64, SR", options(nomem, nostack)) };
 }
 
-impl<SMCLK: SmclkState> ClockConfig<MclkDefined, SMCLK> {
-    #[inline]
-    fn configure_dco_fll(&self) {
-        // Run FLL configuration procedure from the user's guide if we are using DCO
-        if let MclkSel::Dcoclk(target_freq) = self.mclk.0 {
-            fll_off();
-            msp430::asm::nop();
-            msp430::asm::nop();
-            msp430::asm::nop();
-            msp430::asm::nop();
-            msp430::asm::nop();
-            msp430::asm::nop();
+// Run FLL configuration procedure from the user's guide. The software trim is only needed for
+// frequencies that the factory trim isn't calibrated for.
+#[inline]
+fn configure_dco_fll(
+    periph: &pac::CS,
+    dcorsel: DCORSEL_A,
+    multiplier: u16,
+    software_trim: bool,
+) -> Result<(), ClockError> {
+    fll_off();
+    msp430::asm::nop();
+    msp430::asm::nop();
+    msp430::asm::nop();
+    msp430::asm::nop();
+    msp430::asm::nop();
+    msp430::asm::nop();
+
+    periph.csctl3.write(|w| w.selref().refoclk());
+    periph.csctl0.write(|w| unsafe { w.bits(0) });
+    if software_trim {
+        // Enable software trim, starting from the middle DCOFTRIM value of 3
+        let dcorsel_bits = (u8::from(dcorsel) as u16) << 1;
+        periph
+            .csctl1
+            .write(|w| unsafe { w.bits(DCOFTRIMEN | (3 << DCOFTRIM_SHIFT) | dcorsel_bits) });
+    } else {
+        periph.csctl1.write(|w| w.dcorsel().variant(dcorsel));
+    }
+    periph
+        .csctl2
+        .write(|w| unsafe { w.flln().bits(multiplier - 1) }.flld()._1());
+
+    msp430::asm::nop();
+    msp430::asm::nop();
+    msp430::asm::nop();
+    msp430::asm::nop();
+    msp430::asm::nop();
+    msp430::asm::nop();
+    msp430::asm::nop();
+    fll_on();
+
+    if software_trim {
+        software_trim_dco(periph, multiplier as u32 * REFOCLK as u32);
+    }
 
-            self.periph.csctl3.write(|w| w.selref().refoclk());
-            self.periph.csctl0.write(|w| unsafe { w.bits(0) });
-            self.periph
-                .csctl1
-                .write(|w| w.dcorsel().variant(target_freq.dcorsel()));
-            self.periph.csctl2.write(|w| {
-                unsafe { w.flln().bits(target_freq.multiplier() - 1) }
-                    .flld()
-                    ._1()
-            });
+    match wait_fll_lock(periph) {
+        // TI recommends the factory trim of the range if the trimmed DCO won't lock
+        Err(_) if software_trim => configure_dco_fll(periph, dcorsel, multiplier, false),
+        res => res,
+    }
+}
 
+// Bound on attempts to clear the crystal fault flags. A watch crystal can take hundreds of
+// milliseconds to start, which this covers at the default MCLK frequency.
+const XT1_START_ATTEMPTS: u32 = 50_000;
+
+// Start the crystal oscillator at full drive strength and wait for its fault flag to stay clear.
+// The oscillator fault flag in SFRIFG1 is cleared along with XT1OFFG, since it's set by any
+// oscillator fault.
+fn start_lfxt(periph: &pac::CS) -> Result<(), ClockError> {
+    periph.csctl6.modify(|_, w| {
+        w.xts()
+            .clear_bit()
+            .xt1bypass()
+            .clear_bit()
+            .xt1drive()
+            .bits(0b11)
+    });
+    let sfr = unsafe { &*pac::SFR::ptr() };
+    for _ in 0..XT1_START_ATTEMPTS {
+        unsafe {
+            periph.csctl7.clear_bits(|w| w.xt1offg().clear_bit());
+            sfr.sfrifg1.clear_bits(|w| w.ofifg().clear_bit());
+        }
+        if periph.csctl7.read().xt1offg().bit_is_clear() {
+            return Ok(());
+        }
+    }
+    Err(ClockError::Xt1Fault)
+}
+
+// Bound on polls of the lock status. The FLL takes up to 280 reference clock cycles to lock,
+// which is under 210k MCLK cycles even at 24 MHz, and each poll takes several cycles.
+const FLL_LOCK_POLLS: u32 = 100_000;
+
+// Some chips get stuck with the DCO fault flag set or with FLLUNLOCK reporting a DCO error, so
+// the lock wait is bounded instead of spinning forever
+fn wait_fll_lock(periph: &pac::CS) -> Result<(), ClockError> {
+    for _ in 0..FLL_LOCK_POLLS {
+        let ctl7 = periph.csctl7.read();
+        if ctl7.dcoffg().bit_is_set() {
+            unsafe { periph.csctl7.clear_bits(|w| w.dcoffg().clear_bit()) };
+        } else if ctl7.fllunlock().is_fllunlock_0() {
+            return Ok(());
+        }
+    }
+    Err(ClockError::FllUnlock)
+}
+
+const DCOFTRIMEN: u16 = 1 << 7;
+const DCOFTRIM_SHIFT: u16 = 4;
+const DCOFTRIM_MASK: u16 = 0b111 << DCOFTRIM_SHIFT;
+const DCOTAP_MASK: u16 = 0x01FF;
+const DCOTAP_MID: u16 = 256;
+
+// Software DCO trim from the user's guide. The FLL settles at a DCOTAP value, and DCOFTRIM is
+// stepped until DCOTAP crosses the middle of its range, leaving the most headroom for the FLL to
+// track temperature and voltage drift. The settings with DCOTAP closest to the middle are kept.
+fn software_trim_dco(periph: &pac::CS, dco_freq: u32) {
+    let mut old_tap = u16::MAX;
+    let mut best_delta = u16::MAX;
+    let (mut best_ctl0, mut best_ctl1) = (0, 0);
+    // Wait for around 24 cycles of the FLL reference clock, per the user's guide
+    let settle_nops = 3 * (dco_freq / 1000);
+
+    loop {
+        periph.csctl0.write(|w| unsafe { w.bits(DCOTAP_MID) });
+        unsafe { periph.csctl7.clear_bits(|w| w.dcoffg().clear_bit()) };
+        for _ in 0..settle_nops {
             msp430::asm::nop();
-            msp430::asm::nop();
-            msp430::asm::nop();
-            msp430::asm::nop();
-            msp430::asm::nop();
-            msp430::asm::nop();
-            msp430::asm::nop();
-            fll_on();
+        }
+        for _ in 0..FLL_LOCK_POLLS {
+            let ctl7 = periph.csctl7.read();
+            if ctl7.fllunlock().is_fllunlock_0() || ctl7.dcoffg().bit_is_set() {
+                break;
+            }
+        }
 
-            while !self.periph.csctl7.read().fllunlock().is_fllunlock_0() {}
+        let ctl0 = periph.csctl0.read().bits();
+        let ctl1 = periph.csctl1.read().bits();
+        let new_tap = ctl0 & DCOTAP_MASK;
+        let trim = (ctl1 & DCOFTRIM_MASK) >> DCOFTRIM_SHIFT;
+
+        // Stop once DCOTAP crosses the middle, or DCOFTRIM can't move any further
+        let (delta, done, next_trim) = if new_tap < DCOTAP_MID {
+            let crossed = old_tap != u16::MAX && old_tap >= DCOTAP_MID;
+            (
+                DCOTAP_MID - new_tap,
+                crossed || trim == 0,
+                trim.wrapping_sub(1),
+            )
+        } else {
+            let crossed = old_tap < DCOTAP_MID;
+            (new_tap - DCOTAP_MID, crossed || trim == 7, trim + 1)
+        };
+        if delta < best_delta {
+            best_ctl0 = ctl0;
+            best_ctl1 = ctl1;
+            best_delta = delta;
+        }
+        if done {
+            break;
+        }
+        periph
+            .csctl1
+            .write(|w| unsafe { w.bits((ctl1 & !DCOFTRIM_MASK) | (next_trim << DCOFTRIM_SHIFT)) });
+        old_tap = new_tap;
+    }
+
+    periph.csctl0.write(|w| unsafe { w.bits(best_ctl0) });
+    periph.csctl1.write(|w| unsafe { w.bits(best_ctl1) });
+}
+
+impl<SMCLK: SmclkState> ClockConfig<MclkDefined, SMCLK> {
+    #[inline]
+    fn start_lfxt(&self) -> Result<(), ClockError> {
+        let aclk_lfxt = matches!(self.aclk_sel, AclkSel::Lfxt(_));
+        let mclk_lfxt = matches!(self.mclk.0, MclkSel::Lfxt(_));
+        if aclk_lfxt || mclk_lfxt {
+            start_lfxt(&self.periph)
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn configure_dco_fll(&self) -> Result<(), ClockError> {
+        match self.mclk.0 {
+            MclkSel::Dcoclk(target_freq) => configure_dco_fll(
+                &self.periph,
+                target_freq.dcorsel(),
+                target_freq.multiplier(),
+                false,
+            ),
+            MclkSel::DcoclkCustom(multiplier) => configure_dco_fll(
+                &self.periph,
+                dcorsel_for(multiplier as u32 * REFOCLK as u32),
+                multiplier,
+                true,
+            ),
+            _ => Ok(()),
         }
     }
 
@@ -328,49 +612,187 @@ impl<SMCLK: SmclkState> ClockConfig<MclkDefined, SMCLK> {
 
     #[inline]
     unsafe fn configure_fram(fram: &mut Fram, mclk_freq: u32) {
-        if mclk_freq > 16_000_000 {
-            fram.set_wait_states(WaitStates::Wait2);
-        } else if mclk_freq > 8_000_000 {
-            fram.set_wait_states(WaitStates::Wait1);
-        } else {
-            fram.set_wait_states(WaitStates::Wait0);
-        }
+        fram.set_wait_states(Fram::wait_states_for(mclk_freq));
+        debug_check_mclk(fram, mclk_freq);
     }
 }
 
+/// Maximum frequency of MCLK and SMCLK supported by the device
+pub const MAX_FREQ: u32 = 24_000_000;
+
+// Catches overclocking and FRAM wait states that are too low for MCLK, either of which causes
+// sporadic instruction fetch faults instead of a clean failure
+#[inline(always)]
+fn debug_check_mclk(fram: &Fram, mclk_freq: u32) {
+    debug_assert!(mclk_freq <= MAX_FREQ, "MCLK exceeds the maximum frequency");
+    debug_assert!(
+        fram.wait_states() >= Fram::wait_states_for(mclk_freq) as u8,
+        "FRAM wait states too low for MCLK"
+    );
+}
+
 impl ClockConfig<MclkDefined, SmclkDefined> {
     /// Apply clock configuration to hardware and return SMCLK and ACLK clock objects.
-    /// Also returns delay provider
+    /// Also returns delay provider. Fails if the FLL doesn't lock.
     #[inline]
-    pub fn freeze(self, fram: &mut Fram) -> (Smclk, Aclk, Delay) {
+    pub fn freeze(self, fram: &mut Fram) -> Result<(Smclk, Aclk, Delay), ClockError> {
+        let (_, smclk, aclk, delay) = self.freeze_all(fram)?;
+        Ok((smclk, aclk, delay))
+    }
+
+    /// Like `freeze()`, but also returns the MCLK clock object
+    #[inline]
+    pub fn freeze_all(self, fram: &mut Fram) -> Result<(Mclk, Smclk, Aclk, Delay), ClockError> {
         let mclk_freq = self.mclk.0.freq() >> (self.mclk_div as u32);
         unsafe { Self::configure_fram(fram, mclk_freq) };
-        self.configure_dco_fll();
+        self.start_lfxt()?;
+        self.configure_dco_fll()?;
         self.configure_cs();
-        (
-            Smclk(mclk_freq >> (self.smclk.0 as u32)),
+        let smclk_freq = mclk_freq >> (self.smclk.0 as u32);
+        debug_assert!(
+            smclk_freq <= MAX_FREQ,
+            "SMCLK exceeds the maximum frequency"
+        );
+        Ok((
+            Mclk(mclk_freq),
+            Smclk(smclk_freq),
             Aclk(self.aclk_sel.freq()),
             Delay::new(mclk_freq),
-        )
+        ))
+    }
+
+    /// Like `freeze()`, but also returns a `DcoScaler` which keeps control of the clock system so
+    /// MCLK can be switched between DCO frequencies at runtime.
+    #[inline]
+    pub fn freeze_scalable(
+        self,
+        fram: &mut Fram,
+    ) -> Result<(DcoScaler, Smclk, Aclk, Delay), ClockError> {
+        let mclk_freq = self.mclk.0.freq() >> (self.mclk_div as u32);
+        unsafe { Self::configure_fram(fram, mclk_freq) };
+        self.start_lfxt()?;
+        self.configure_dco_fll()?;
+        self.configure_cs();
+        let smclk_div = self.smclk.0;
+        let smclk_freq = mclk_freq >> (smclk_div as u32);
+        debug_assert!(
+            smclk_freq <= MAX_FREQ,
+            "SMCLK exceeds the maximum frequency"
+        );
+        let aclk = Aclk(self.aclk_sel.freq());
+        let scaler = DcoScaler {
+            periph: self.periph,
+            mclk_div: self.mclk_div,
+            smclk_div,
+            mclk_freq,
+        };
+        Ok((scaler, Smclk(smclk_freq), aclk, Delay::new(mclk_freq)))
+    }
+}
+
+/// Runtime control over the MCLK frequency, for dropping to a low DCO frequency while idle and
+/// speeding back up for bursts of work. Returned by `ClockConfig::freeze_scalable()`.
+///
+/// Since SMCLK is divided from MCLK, switching frequencies also changes SMCLK. Clock objects and
+/// `Delay` providers from before the switch are stale afterwards, so peripherals clocked from SMCLK
+/// should be idle during the switch and reconfigured using the returned clock objects.
+pub struct DcoScaler {
+    periph: pac::CS,
+    mclk_div: MclkDiv,
+    smclk_div: SmclkDiv,
+    mclk_freq: u32,
+}
+
+impl DcoScaler {
+    /// Current MCLK frequency
+    #[inline]
+    pub fn mclk_freq(&self) -> u32 {
+        self.mclk_freq
+    }
+
+    /// Clock object for the current MCLK frequency
+    #[inline]
+    pub fn mclk(&self) -> Mclk {
+        Mclk(self.mclk_freq)
+    }
+
+    /// Switch MCLK to the DCO running at `target_freq`, keeping the MCLK and SMCLK dividers.
+    /// Returns the new SMCLK clock object and delay provider.
+    ///
+    /// FRAM wait states are increased before speeding up and decreased only after slowing down,
+    /// so instruction fetches from FRAM are never too fast for the current wait state setting.
+    /// If the FLL fails to lock, the wait states are left at the higher of the two settings.
+    pub fn set_mclk(
+        &mut self,
+        target_freq: DcoclkFreqSel,
+        fram: &mut Fram,
+    ) -> Result<(Smclk, Delay), ClockError> {
+        let mclk_freq = target_freq.freq() >> (self.mclk_div as u32);
+        if mclk_freq > self.mclk_freq {
+            unsafe { fram.set_wait_states(Fram::wait_states_for(mclk_freq)) };
+        }
+
+        configure_dco_fll(
+            &self.periph,
+            target_freq.dcorsel(),
+            target_freq.multiplier(),
+            false,
+        )?;
+        self.periph
+            .csctl4
+            .modify(|_, w| w.selms().variant(SELMS_A::DCOCLKDIV));
+
+        if mclk_freq < self.mclk_freq {
+            unsafe { fram.set_wait_states(Fram::wait_states_for(mclk_freq)) };
+        }
+        debug_check_mclk(fram, mclk_freq);
+        self.mclk_freq = mclk_freq;
+        Ok((
+            Smclk(mclk_freq >> (self.smclk_div as u32)),
+            Delay::new(mclk_freq),
+        ))
     }
 }
 
 impl ClockConfig<MclkDefined, SmclkDisabled> {
     /// Apply clock configuration to hardware and return ACLK clock object, as SMCLK is disabled.
-    /// Also returns delay provider.
+    /// Also returns delay provider. Fails if the FLL doesn't lock.
     #[inline]
-    pub fn freeze(self, fram: &mut Fram) -> (Aclk, Delay) {
+    pub fn freeze(self, fram: &mut Fram) -> Result<(Aclk, Delay), ClockError> {
+        let (_, aclk, delay) = self.freeze_all(fram)?;
+        Ok((aclk, delay))
+    }
+
+    /// Like `freeze()`, but also returns the MCLK clock object
+    #[inline]
+    pub fn freeze_all(self, fram: &mut Fram) -> Result<(Mclk, Aclk, Delay), ClockError> {
         let mclk_freq = self.mclk.0.freq() >> (self.mclk_div as u32);
-        self.configure_dco_fll();
+        self.start_lfxt()?;
+        self.configure_dco_fll()?;
         unsafe { Self::configure_fram(fram, mclk_freq) };
         self.configure_cs();
-        (Aclk(self.aclk_sel.freq()), Delay::new(mclk_freq))
+        Ok((
+            Mclk(mclk_freq),
+            Aclk(self.aclk_sel.freq()),
+            Delay::new(mclk_freq),
+        ))
     }
 }
 
-/// SMCLK clock object
+/// MCLK clock object, carrying the CPU clock frequency after the MCLK divider. Like the other clock
+/// objects, it's `Copy`. `DcoScaler::mclk()` returns a fresh one after a frequency switch.
+#[derive(Clone, Copy)]
+pub struct Mclk(u32);
+/// SMCLK clock object. Its frequency is always the MCLK frequency divided by the SMCLK divider.
+///
+/// Clock objects are `Copy`, so they can be passed freely through initialization code without
+/// needing access to the CS peripheral again. Since `freeze()` consumes the CS peripheral, the
+/// clocks can't be reconfigured afterwards, so the frequency carried by a clock object never goes
+/// stale. The exception is `DcoScaler::set_mclk()`, which returns a fresh `Smclk` to use instead.
+#[derive(Clone, Copy)]
 pub struct Smclk(u32);
-/// ACLK clock object
+/// ACLK clock object. Like `Smclk`, it's `Copy` and carries its own frequency.
+#[derive(Clone, Copy)]
 pub struct Aclk(u16);
 
 /// Trait for configured clock objects
@@ -380,6 +802,35 @@ pub trait Clock {
 
     /// Frequency of the clock
     fn freq(&self) -> Self::Freq;
+
+    /// Pick the smallest divisor that brings the clock frequency down to at most `target_hz`,
+    /// which is useful for computing the clock divisors of peripherals such as I2C and SPI.
+    /// Returns the divisor along with the frequency actually achieved with it.
+    ///
+    /// The divisor is clamped between 1 and `u16::MAX`, so the achieved frequency may still be
+    /// above the target if the target is very low.
+    #[inline]
+    fn divisor_for(&self, target_hz: u32) -> (u16, u32)
+    where
+        Self::Freq: Into<u32>,
+    {
+        let freq: u32 = self.freq().into();
+        let div = match target_hz {
+            0 => u16::MAX as u32,
+            // Round up so the achieved frequency never exceeds the target
+            _ => (freq / target_hz + (freq % target_hz != 0) as u32).clamp(1, u16::MAX as u32),
+        };
+        (div as u16, freq / div)
+    }
+}
+
+impl Clock for Mclk {
+    type Freq = u32;
+
+    #[inline]
+    fn freq(&self) -> u32 {
+        self.0
+    }
 }
 
 impl Clock for Smclk {
@@ -1,5 +1,14 @@
 //! Embedded hal delay implementation
-use crate::hal::blocking::delay::DelayMs;
+//!
+//! `Delay` is returned when freezing the clock configuration and busy-waits by executing a number
+//! of NOPs derived from the MCLK frequency. It's cheap, but only approximately accurate.
+//!
+//! `TimerDelay` instead takes ownership of a spare TimerB peripheral and waits on the timer count,
+//! so its delays are accurate to the timer clock regardless of MCLK or code generation. It can also
+//! be built from a `TimerConfig` with clock dividers, trading resolution for a slower counter.
+use crate::clock::{Aclk, Clock, Smclk};
+use crate::hal::blocking::delay::{DelayMs, DelayUs};
+use crate::timer::{TimerConfig, TimerPeriph};
 use msp430::asm;
 
 /// Delay provider struct
@@ -29,3 +38,111 @@ impl DelayMs<u16> for Delay {
         }
     }
 }
+
+/// Delay provider backed by a free-running TimerB peripheral
+pub struct TimerDelay<T: TimerPeriph> {
+    timer: T,
+    freq: u32,
+}
+
+impl<T: TimerPeriph> TimerDelay<T> {
+    /// Create a delay provider from a timer clocked by SMCLK
+    #[inline]
+    pub fn smclk(timer: T, smclk: &Smclk) -> Self {
+        Self::new(timer, TimerConfig::smclk(smclk), smclk.freq())
+    }
+
+    /// Create a delay provider from a timer clocked by ACLK
+    #[inline]
+    pub fn aclk(timer: T, aclk: &Aclk) -> Self {
+        Self::new(timer, TimerConfig::aclk(aclk), aclk.freq() as u32)
+    }
+
+    /// Create a delay provider from a timer configuration, including any clock dividers. Returns
+    /// `None` if the timer is clocked from TBCLK, since its frequency is unknown.
+    #[inline]
+    pub fn with_config(timer: T, config: TimerConfig<T>) -> Option<Self> {
+        let freq = config.tick_freq()?;
+        Some(Self::new(timer, config, freq))
+    }
+
+    #[inline]
+    fn new(timer: T, config: TimerConfig<T>, freq: u32) -> Self {
+        config.write_regs(&timer);
+        timer.continuous();
+        TimerDelay { timer, freq }
+    }
+
+    /// Stop the timer and release the underlying peripheral
+    #[inline]
+    pub fn free(self) -> T {
+        self.timer.stop();
+        self.timer
+    }
+
+    // ACLK is asynchronous to MCLK, so read the counter until two consecutive reads agree
+    #[inline(always)]
+    fn count(&self) -> u16 {
+        let mut prev = self.timer.tbxr_rd();
+        loop {
+            let curr = self.timer.tbxr_rd();
+            if curr == prev {
+                return curr;
+            }
+            prev = curr;
+        }
+    }
+
+    // Wrapping subtraction of successive counts keeps this correct across any number of timer
+    // overflows, as long as the counter is polled at least once per overflow period
+    fn delay_ticks(&self, ticks: u64) {
+        let mut remaining = ticks;
+        let mut last = self.count();
+        while remaining > 0 {
+            let now = self.count();
+            remaining = remaining.saturating_sub(now.wrapping_sub(last) as u64);
+            last = now;
+        }
+    }
+
+    // Round up so the delay is never shorter than requested
+    #[inline]
+    fn ticks(&self, amount: u32, per_sec: u32) -> u64 {
+        (amount as u64 * self.freq as u64 + (per_sec as u64 - 1)) / per_sec as u64
+    }
+
+    /// Delay for at least `ns` nanoseconds. The delay is rounded up to a whole number of timer
+    /// ticks, so short delays are limited by the timer resolution.
+    #[inline]
+    pub fn delay_ns(&mut self, ns: u32) {
+        self.delay_ticks(self.ticks(ns, 1_000_000_000));
+    }
+}
+
+impl<T: TimerPeriph> DelayUs<u32> for TimerDelay<T> {
+    #[inline]
+    fn delay_us(&mut self, us: u32) {
+        self.delay_ticks(self.ticks(us, 1_000_000));
+    }
+}
+
+impl<T: TimerPeriph> DelayUs<u16> for TimerDelay<T> {
+    #[inline]
+    fn delay_us(&mut self, us: u16) {
+        self.delay_us(us as u32);
+    }
+}
+
+impl<T: TimerPeriph> DelayMs<u32> for TimerDelay<T> {
+    #[inline]
+    fn delay_ms(&mut self, ms: u32) {
+        self.delay_ticks(self.ticks(ms, 1_000));
+    }
+}
+
+impl<T: TimerPeriph> DelayMs<u16> for TimerDelay<T> {
+    #[inline]
+    fn delay_ms(&mut self, ms: u16) {
+        self.delay_ms(ms as u32);
+    }
+}
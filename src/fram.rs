@@ -1,4 +1,9 @@
 //! FRAM controller
+//!
+//! FRAM can only be accessed at up to 8MHz, so faster MCLK frequencies require wait states.
+//! `ClockConfig::freeze()` sets these automatically. Any other change to the MCLK frequency must be
+//! accompanied by a matching wait state update, applied *before* speeding MCLK up and *after*
+//! slowing it down, using `Fram::wait_states_for()` to find the required setting.
 
 use msp430fr2355 as pac;
 use pac::FRCTL;
@@ -38,6 +43,25 @@ pub enum WaitStates {
 }
 
 impl Fram {
+    /// Minimum number of FRAM wait states required to run MCLK at `mclk_hz`. One wait state is
+    /// needed above 8MHz and two above 16MHz.
+    #[inline]
+    pub const fn wait_states_for(mclk_hz: u32) -> WaitStates {
+        if mclk_hz > 16_000_000 {
+            WaitStates::Wait2
+        } else if mclk_hz > 8_000_000 {
+            WaitStates::Wait1
+        } else {
+            WaitStates::Wait0
+        }
+    }
+
+    /// Number of FRAM wait states currently configured
+    #[inline]
+    pub fn wait_states(&self) -> u8 {
+        self.periph.frctl0.read().nwaits().bits()
+    }
+
     /// Set number of FRAM wait states. Could cause issues reading instructions from FRAM if
     /// incorrect. Should wait 1 cycle if MCLK > 8MHz and 2 cycles if MCLK > 16MHz.
     #[inline]
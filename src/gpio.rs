@@ -11,6 +11,32 @@
 //! conversions on each pin is limited by the hardware capabilities in the [`datasheet`], so not
 //! every pin in every configuration can be converted to every alternate functionality.
 //!
+//! Pins are neither `Clone` nor `Copy`, and every configuration method takes the pin by value.
+//! Peripherals claim their pins the same way, through `Into` conversions that consume the pin, so
+//! handing the same physical pin to two peripherals (e.g. using P4.6 as both I2C SDA and SPI
+//! MOSI) fails to compile with a "use of moved value" error:
+//!
+//! ```compile_fail,E0382
+//! use msp430fr2x5x_hal::{gpio::Batch, i2c::UsciB1SDAPin, pac, pmm::Pmm, spi::UsciB1MOSIPin};
+//!
+//! let periph = pac::Peripherals::take().unwrap();
+//! let pmm = Pmm::new(periph.PMM);
+//! let p4 = Batch::new(periph.P4).split(&pmm);
+//! let pin = p4.pin6.to_alternate1();
+//! let sda: UsciB1SDAPin = pin.into();
+//! let mosi: UsciB1MOSIPin = pin.into(); // error[E0382]: use of moved value: `pin`
+//! ```
+//!
+//! Output pins can be turned into emulated open-drain outputs with `into_open_drain()`, which
+//! releases the line by switching the pin to an input instead of driving it high.
+//!
+//! To change several output pins on one port at the same instant, borrow them into a `PortOutput`
+//! handle, which writes them all with a single masked `PxOUT` write.
+//!
+//! With the `async` feature enabled, interrupt-capable input pins can also be awaited with
+//! `wait_for_rising_edge()` and `wait_for_falling_edge()`, as long as the port's ISR calls
+//! `wake_on_interrupt()`.
+//!
 //! [`datasheet`]: http://www.ti.com/lit/ds/symlink/msp430fr2355.pdf
 
 pub use crate::batch_gpio::*;
@@ -133,6 +159,11 @@ pub struct Pulldown;
 /// Pull typestate for floating inputs
 pub struct Floating;
 
+/// Typestate for emulated open-drain outputs, created by `Pin::into_open_drain()` or
+/// `Pin::into_open_drain_pullup()`. The type parameter specifies the pull applied while the pin is
+/// released.
+pub struct OpenDrain<PULL>(PhantomData<PULL>);
+
 /// A single GPIO pin.
 pub struct Pin<PORT: PortNum, PIN: PinNum, DIR> {
     _port: PhantomData<PORT>,
@@ -227,6 +258,22 @@ impl<PORT: IntrPortNum, PIN: PinNum, PULL> Pin<PORT, PIN, Input<PULL>> {
         self
     }
 
+    /// Configure the pin to wake the CPU from a low power mode on the given edge, clearing any
+    /// stale interrupt flag first. Port interrupts don't need a clock, so they wake the CPU from
+    /// every mode in the `lpm` module, including LPM4. Pins can only be obtained after `Pmm` has
+    /// cleared LOCKLPM5, so no further unlocking is needed.
+    ///
+    /// The port's ISR must clear the flag, e.g. by reading `PxIV`, or it will fire again as soon
+    /// as it returns. See the `lpm` module for how the CPU goes back to sleep afterwards.
+    #[inline]
+    pub fn enable_wakeup(&mut self, edge: Edge) -> &mut Self {
+        match edge {
+            Edge::Rising => self.select_rising_edge_trigger(),
+            Edge::Falling => self.select_falling_edge_trigger(),
+        };
+        self.enable_interrupts()
+    }
+
     /// Set interrupt flag high, triggering an ISR if interrupts are enabled.
     #[inline]
     pub fn set_ifg(&mut self) -> &mut Self {
@@ -256,6 +303,90 @@ impl<PORT: IntrPortNum, PIN: PinNum, PULL> Pin<PORT, PIN, Input<PULL>> {
     }
 }
 
+#[cfg(feature = "async")]
+mod wakers {
+    use core::cell::UnsafeCell;
+    use core::task::Waker;
+
+    struct WakerSlot(UnsafeCell<Option<Waker>>);
+    // Slots are only accessed inside critical sections
+    unsafe impl Sync for WakerSlot {}
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: WakerSlot = WakerSlot(UnsafeCell::new(None));
+    // One slot for each pin of the 4 interrupt-capable ports
+    static WAKERS: [WakerSlot; 32] = [EMPTY; 32];
+
+    pub(super) fn register(idx: usize, waker: &Waker) {
+        msp430::interrupt::free(|_| {
+            let slot = unsafe { &mut *WAKERS[idx].0.get() };
+            match slot {
+                Some(old) if old.will_wake(waker) => {}
+                _ => *slot = Some(waker.clone()),
+            }
+        })
+    }
+
+    pub(super) fn wake(idx: usize) {
+        let waker = msp430::interrupt::free(|_| unsafe { (*WAKERS[idx].0.get()).take() });
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<PORT: IntrPortNum, PIN: PinNum, PULL> Pin<PORT, PIN, Input<PULL>> {
+    /// Wait for a rising edge on the pin. The port's ISR must call `wake_on_interrupt()` for the
+    /// returned future to be woken.
+    #[inline]
+    pub async fn wait_for_rising_edge(&mut self) {
+        self.select_rising_edge_trigger();
+        self.wait_for_edge().await
+    }
+
+    /// Wait for a falling edge on the pin. The port's ISR must call `wake_on_interrupt()` for the
+    /// returned future to be woken.
+    #[inline]
+    pub async fn wait_for_falling_edge(&mut self) {
+        self.select_falling_edge_trigger();
+        self.wait_for_edge().await
+    }
+
+    async fn wait_for_edge(&mut self) {
+        core::future::poll_fn(|cx| {
+            // Register before enabling interrupts so an edge can't slip in between and go unnoticed
+            wakers::register(PORT::INTR_IDX * 8 + PIN::NUM as usize, cx.waker());
+            self.enable_interrupts();
+            match self.wait_for_ifg() {
+                Ok(()) => core::task::Poll::Ready(()),
+                Err(_) => core::task::Poll::Pending,
+            }
+        })
+        .await;
+        self.disable_interrupt();
+    }
+}
+
+/// Wake any tasks waiting on an edge of a pin on this port. Call this from the port's ISR when
+/// using `wait_for_rising_edge()` or `wait_for_falling_edge()`.
+///
+/// Interrupts are disabled on the pins that fired, so the ISR doesn't fire again, but their
+/// interrupt flags are left set for the woken tasks to observe. Don't read `PxIV` before calling
+/// this, since that clears the flags.
+#[cfg(feature = "async")]
+#[inline]
+pub fn wake_on_interrupt<PORT: IntrPortNum>() {
+    let p = unsafe { PORT::steal() };
+    let fired = p.pxifg_rd() & p.pxie_rd();
+    p.pxie_clear(!fired);
+    for pin in 0..8 {
+        if fired.check(pin) != 0 {
+            wakers::wake(PORT::INTR_IDX * 8 + pin as usize);
+        }
+    }
+}
+
 /// Interrupt vector register used to determine which pin caused a port ISR
 pub struct PxIV<PORT: PortNum>(PhantomData<PORT>);
 
@@ -339,6 +470,81 @@ impl<PORT: PortNum, PIN: PinNum> Pin<PORT, PIN, Output> {
     }
 }
 
+impl<PORT: PortNum, PIN: PinNum> Pin<PORT, PIN, Output> {
+    /// Configures pin as an emulated open-drain output, for buses such as 1-Wire or bit-banged I2C.
+    /// The pin is driven low by switching it to an output, and released by switching it back to a
+    /// floating input, so the line needs an external pullup. The pin starts out released.
+    #[inline]
+    pub fn into_open_drain(self) -> Pin<PORT, PIN, OpenDrain<Floating>> {
+        let p = unsafe { PORT::steal() };
+        p.pxdir_clear(PIN::CLR_MASK);
+        p.pxren_clear(PIN::CLR_MASK);
+        p.pxout_clear(PIN::CLR_MASK);
+        make_pin!()
+    }
+
+    /// Like `into_open_drain()`, but enables the internal pullup while the pin is released, so no
+    /// external pullup is needed. The internal pullup is weak, so edges will be slow on long lines.
+    #[inline]
+    pub fn into_open_drain_pullup(self) -> Pin<PORT, PIN, OpenDrain<Pullup>> {
+        let p = unsafe { PORT::steal() };
+        p.pxdir_clear(PIN::CLR_MASK);
+        p.pxout_set(PIN::SET_MASK);
+        p.pxren_set(PIN::SET_MASK);
+        make_pin!()
+    }
+}
+
+impl<PORT: PortNum, PIN: PinNum, PULL> Pin<PORT, PIN, OpenDrain<PULL>> {
+    /// Configures pin as a regular push-pull output. The output level is left low.
+    #[inline]
+    pub fn into_push_pull(self) -> Pin<PORT, PIN, Output> {
+        let p = unsafe { PORT::steal() };
+        p.pxout_clear(PIN::CLR_MASK);
+        p.pxdir_set(PIN::SET_MASK);
+        make_pin!()
+    }
+}
+
+// While driven low the pin is an output with PxOUT cleared. While released it's an input, with
+// PxOUT set so that the internal pullup applies if PxREN is enabled.
+impl<PORT: PortNum, PIN: PinNum, PULL> OutputPin for Pin<PORT, PIN, OpenDrain<PULL>> {
+    type Error = void::Void;
+
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        let p = unsafe { PORT::steal() };
+        p.pxout_clear(PIN::CLR_MASK);
+        p.pxdir_set(PIN::SET_MASK);
+        Ok(())
+    }
+
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        let p = unsafe { PORT::steal() };
+        p.pxdir_clear(PIN::CLR_MASK);
+        p.pxout_set(PIN::SET_MASK);
+        Ok(())
+    }
+}
+
+// Reads the actual level of the line, which may be held low by another device even while the pin
+// is released.
+impl<PORT: PortNum, PIN: PinNum, PULL> InputPin for Pin<PORT, PIN, OpenDrain<PULL>> {
+    type Error = void::Void;
+
+    #[inline]
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        let p = unsafe { PORT::steal() };
+        Ok(p.pxin_rd().check(PIN::NUM) != 0)
+    }
+
+    #[inline]
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.is_high().map(|r| !r)
+    }
+}
+
 impl<PORT: PortNum, PIN: PinNum, PULL> InputPin for Pin<PORT, PIN, Input<PULL>> {
     type Error = void::Void;
 
@@ -396,6 +602,70 @@ impl<PORT: PortNum, PIN: PinNum> ToggleableOutputPin for Pin<PORT, PIN, Output>
     }
 }
 
+/// Handle for writing several output pins on the same port at once, for bit-banging parallel buses.
+///
+/// Pins are added with `with()`, which borrows them mutably, so they stay outputs for as long as the
+/// handle exists. Writes only ever touch the pins that were added.
+pub struct PortOutput<'a, PORT: PortNum> {
+    mask: u8,
+    _pins: PhantomData<&'a mut PORT>,
+}
+
+impl<'a, PORT: PortNum> PortOutput<'a, PORT> {
+    /// Create a handle that doesn't control any pins yet
+    #[inline]
+    pub fn new() -> Self {
+        PortOutput {
+            mask: 0,
+            _pins: PhantomData,
+        }
+    }
+
+    /// Add an output pin to the handle
+    #[inline]
+    pub fn with<PIN: PinNum>(mut self, _pin: &'a mut Pin<PORT, PIN, Output>) -> Self {
+        self.mask |= PIN::SET_MASK;
+        self
+    }
+
+    /// Bitmask of the pins controlled by this handle
+    #[inline]
+    pub fn mask(&self) -> u8 {
+        self.mask
+    }
+
+    /// Set every pin in the handle to the corresponding bit of `value` with a single write to `PxOUT`
+    #[inline]
+    pub fn write(&mut self, value: u8) {
+        self.write_masked(value, 0xFF);
+    }
+
+    /// Set the pins selected by `mask` to the corresponding bits of `value` with a single write to
+    /// `PxOUT`. Bits of pins that weren't added to the handle are ignored. The read-modify-write is
+    /// done in a critical section, so it can't clobber pins changed from an interrupt.
+    #[inline]
+    pub fn write_masked(&mut self, value: u8, mask: u8) {
+        let p = unsafe { PORT::steal() };
+        let mask = mask & self.mask;
+        msp430::interrupt::free(|_| p.pxout_wr((p.pxout_rd() & !mask) | (value & mask)));
+    }
+
+    /// Toggle the pins selected by `mask` with a single atomic instruction. Bits of pins that weren't
+    /// added to the handle are ignored.
+    #[inline]
+    pub fn toggle(&mut self, mask: u8) {
+        let p = unsafe { PORT::steal() };
+        p.pxout_toggle(mask & self.mask);
+    }
+}
+
+impl<'a, PORT: PortNum> Default for PortOutput<'a, PORT> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// GPIO parts for a specific port, including all 8 pins.
 pub struct Parts<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7> {
     /// Pin0
@@ -427,6 +697,14 @@ impl<PORT: PortNum, DIR0, DIR1, DIR2, DIR3, DIR4, DIR5, DIR6, DIR7>
         Batch::create()
     }
 
+    /// Consumes all pins and the interrupt vector to give back the port peripheral, so that the
+    /// entire port can be configured again from scratch with `Batch::new()`. This is useful when
+    /// GPIO state has to be re-established, such as when waking from LPMx.5.
+    #[inline]
+    pub fn free(self) -> PORT {
+        unsafe { PORT::steal() }
+    }
+
     #[inline]
     pub(super) fn new() -> Self {
         Self {
@@ -497,6 +775,9 @@ pub struct Alternate2<DIR>(PhantomData<DIR>);
 /// Typestate for GPIO alternate function 3
 pub struct Alternate3<DIR>(PhantomData<DIR>);
 
+/// Typestate for pins in analog mode, with the digital input buffer disconnected
+pub struct Analog;
+
 // Sealing these traits takes a lot of work, and I'll never add any items in the future, so they
 // are unsealed
 /// Marker trait for all Pins that have alternate function 1 available
@@ -542,6 +823,32 @@ where
     }
 }
 
+impl<PORT: PortNum, PIN: PinNum, DIR: GpioFunction> Pin<PORT, PIN, DIR>
+where
+    Self: ToAlternate3,
+{
+    /// Convert pin to analog mode for use with the ADC or other analog peripherals. The pin is
+    /// made a floating input before selecting the analog function, which disconnects the digital
+    /// input buffer and avoids the leakage current it would otherwise draw.
+    #[inline]
+    pub fn to_analog(mut self) -> Pin<PORT, PIN, Analog> {
+        let p = unsafe { PORT::steal() };
+        p.pxdir_clear(PIN::CLR_MASK);
+        p.pxren_clear(PIN::CLR_MASK);
+        self.flip_selc();
+        make_pin!()
+    }
+}
+
+impl<PORT: PortNum, PIN: PinNum> Pin<PORT, PIN, Analog> {
+    /// Convert pin back to a floating GPIO input
+    #[inline]
+    pub fn to_gpio(mut self) -> Pin<PORT, PIN, Input<Floating>> {
+        self.flip_selc();
+        make_pin!()
+    }
+}
+
 // sel0 = 1, sel1 = 0
 impl<PORT: PortNum, PIN: PinNum, DIR> Pin<PORT, PIN, Alternate1<DIR>> {
     /// Convert pin to GPIO function
@@ -101,6 +101,8 @@ pub struct UcaCtlw0 {
     pub ucspb: bool,
     pub ucssel: Ucssel,
     pub ucrxeie: bool,
+    pub ucbrkie: bool,
+    pub ucmode: u8,
 }
 
 reg_struct! {
@@ -232,6 +234,9 @@ pub trait EUsciUart: Steal {
     // only call while in reset state
     fn ctl0_settings(&self, reg: UcaCtlw0);
 
+    // enters and leaves the reset state, preserving all other settings
+    fn framing_settings(&self, ucpen: bool, ucpar: bool, ucspb: bool);
+
     fn mctlw_settings(&self, ucos16: bool, ucbrs: u8, ucbrf: u8);
 
     fn statw_rd(&self) -> <Self as EUsciUart>::Statw;
@@ -240,6 +245,11 @@ pub trait EUsciUart: Steal {
     fn txie_clear(&self);
     fn rxie_set(&self);
     fn rxie_clear(&self);
+
+    fn txaddr_set(&self);
+    fn txbrk_set(&self);
+    fn dorm_set(&self);
+    fn dorm_clear(&self);
 }
 
 pub trait EUsciI2C: Steal {
@@ -255,6 +265,8 @@ pub trait EUsciI2C: Steal {
 
     fn set_ucsla10(&self, bit: bool);
     fn set_uctr(&self, bit: bool);
+    // Modify only when UCSWRST = 1
+    fn set_ucmst(&self, bit: bool);
 
     fn txifg0_rd(&self) -> bool;
     fn rxifg0_rd(&self) -> bool;
@@ -271,6 +283,10 @@ pub trait EUsciI2C: Steal {
 
     // Modify only when UCSWRST = 1
     fn ctw1_wr(&self, reg: &UcbCtlw1);
+    // Modify only when UCSWRST = 1
+    fn set_ucastp(&self, ucastp: Ucastp);
+
+    fn ucbcnt_rd(&self) -> u8;
 
     // Modify only when UCSWRST = 1
     fn brw_rd(&self) -> u16;
@@ -298,9 +314,12 @@ pub trait EUsciI2C: Steal {
     fn i2csa_wr(&self, val: u16);
 
     fn ie_wr(&self, reg: &UcbIe);
+    fn bcntie_set(&self);
+    fn bcntie_clear(&self);
 
     fn ifg_rd(&self) -> Self::IfgOut;
     fn ifg_wr(&self, reg: &UcbIFG);
+    fn alifg_nackifg_clr(&self);
     fn iv_rd(&self) -> u16;
 }
 
@@ -313,7 +332,9 @@ pub trait EusciSPI: Steal {
     fn ctw0_wr(&self, reg: &UcxSpiCtw0);
 
     fn set_spi_mode(&self, mode: Mode);
+    fn set_msb_first(&self, msb_first: bool);
 
+    fn brw_rd(&self) -> u16;
     fn brw_wr(&self, val: u16);
 
     fn uclisten_set(&self);
@@ -331,12 +352,17 @@ pub trait EusciSPI: Steal {
 
     fn clear_receive_interrupt(&self);
 
+    fn ie_rd(&self) -> u16;
+    fn ie_wr(&self, bits: u16);
+
     fn transmit_flag(&self) -> bool;
 
     fn receive_flag(&self) -> bool;
 
     fn overrun_flag(&self) -> bool;
 
+    fn busy_flag(&self) -> bool;
+
     fn iv_rd(&self) -> u16;
 }
 
@@ -346,6 +372,7 @@ pub trait UartUcxStatw {
     fn ucpe(&self) -> bool;
     fn ucbrk(&self) -> bool;
     fn ucbusy(&self) -> bool;
+    fn ucaddr(&self) -> bool;
 }
 
 pub trait SpiStatw {
@@ -356,6 +383,8 @@ pub trait SpiStatw {
 }
 
 pub trait I2CUcbIfgOut {
+    /// Clock low timeout interrupt flag
+    fn uccltoifg(&self) -> bool;
     /// Byte counter interrupt flag
     fn ucbcntifg(&self) -> bool;
     /// Not-acknowledge received interrupt flag
@@ -401,6 +430,11 @@ macro_rules! eusci_impl {
                 self.$ucxctlw0().write(UcxSpiCtw0_wr! {reg});
             }
 
+            #[inline(always)]
+            fn brw_rd(&self) -> u16 {
+                self.$ucxbrw().read().bits()
+            }
+
             #[inline(always)]
             fn brw_wr(&self, val: u16) {
                 self.$ucxbrw().write(|w| unsafe { w.bits(val) });
@@ -461,6 +495,22 @@ macro_rules! eusci_impl {
                     .ucckpl().bit(ucckpl));
             }
 
+            #[inline(always)]
+            // Set the bit order without disturbing the rest of the register.
+            fn set_msb_first(&self, msb_first: bool) {
+                self.$ucxctlw0().modify(|_, w| w.ucmsb().bit(msb_first));
+            }
+
+            #[inline(always)]
+            fn ie_rd(&self) -> u16 {
+                self.$ucxie().read().bits()
+            }
+
+            #[inline(always)]
+            fn ie_wr(&self, bits: u16) {
+                self.$ucxie().write(|w| unsafe { w.bits(bits) });
+            }
+
             #[inline(always)]
             fn transmit_flag(&self) -> bool {
                 self.$ucxifg().read().uctxifg().bit()
@@ -476,6 +526,11 @@ macro_rules! eusci_impl {
                 self.$ucxstatw().read().ucoe().bit()
             }
 
+            #[inline(always)]
+            fn busy_flag(&self) -> bool {
+                self.$ucxstatw().read().ucbusy().bit()
+            }
+
             #[inline(always)]
             fn iv_rd(&self) -> u16 {
                 self.$ucxiv().read().uciv().bits()
@@ -548,9 +603,24 @@ macro_rules! eusci_a_impl {
                         .bits(reg.ucssel as u8)
                         .ucrxeie()
                         .bit(reg.ucrxeie)
+                        .ucbrkie()
+                        .bit(reg.ucbrkie)
+                        .ucmode()
+                        .bits(reg.ucmode)
                 });
             }
 
+            #[inline(always)]
+            fn framing_settings(&self, ucpen: bool, ucpar: bool, ucspb: bool) {
+                // Entering reset clears the interrupt enables, so restore them afterwards
+                let ie = self.$ucaxie().read().bits();
+                unsafe { self.$ucaxctlw0().set_bits(|w| w.ucswrst().set_bit()) };
+                self.$ucaxctlw0()
+                    .modify(|_, w| w.ucpen().bit(ucpen).ucpar().bit(ucpar).ucspb().bit(ucspb));
+                unsafe { self.$ucaxctlw0().clear_bits(|w| w.ucswrst().clear_bit()) };
+                self.$ucaxie().write(|w| unsafe { w.bits(ie) });
+            }
+
             #[inline(always)]
             fn mctlw_settings(&self, ucos16: bool, ucbrs: u8, ucbrf: u8) {
                 self.$ucaxmctlw.write(|w| unsafe {
@@ -588,6 +658,26 @@ macro_rules! eusci_a_impl {
                 unsafe { self.$ucaxie().clear_bits(|w| w.ucrxie().clear_bit()) };
             }
 
+            #[inline(always)]
+            fn txaddr_set(&self) {
+                unsafe { self.$ucaxctlw0().set_bits(|w| w.uctxaddr().set_bit()) };
+            }
+
+            #[inline(always)]
+            fn txbrk_set(&self) {
+                unsafe { self.$ucaxctlw0().set_bits(|w| w.uctxbrk().set_bit()) };
+            }
+
+            #[inline(always)]
+            fn dorm_set(&self) {
+                unsafe { self.$ucaxctlw0().set_bits(|w| w.ucdorm().set_bit()) };
+            }
+
+            #[inline(always)]
+            fn dorm_clear(&self) {
+                unsafe { self.$ucaxctlw0().clear_bits(|w| w.ucdorm().clear_bit()) };
+            }
+
             #[inline(always)]
             fn ctl0_reset(&self) {
                 self.$ucaxctlw0().write(|w| w.ucswrst().set_bit());
@@ -655,6 +745,11 @@ macro_rules! eusci_a_impl {
             fn ucbusy(&self) -> bool {
                 self.ucbusy().bit()
             }
+
+            #[inline(always)]
+            fn ucaddr(&self) -> bool {
+                self.ucaddr_ucidle().bit()
+            }
         }
     };
 }
@@ -723,7 +818,7 @@ macro_rules! eusci_b_impl {
 
             #[inline(always)]
             fn uctxstt_rd(&self) -> bool {
-                self.$ucbxctlw0().read().uctxstp().bit()
+                self.$ucbxctlw0().read().uctxstt().bit()
             }
 
             #[inline(always)]
@@ -747,6 +842,14 @@ macro_rules! eusci_b_impl {
                 }
             }
 
+            #[inline(always)]
+            fn set_ucmst(&self, bit: bool) {
+                match bit {
+                    true => unsafe { self.$ucbxctlw0().set_bits(|w| w.ucmst().set_bit()) },
+                    false => unsafe { self.$ucbxctlw0().clear_bits(|w| w.ucmst().clear_bit()) },
+                }
+            }
+
             #[inline(always)]
             fn txifg0_rd(&self) -> bool {
                 self.$ucbxifg().read().uctxifg0().bit()
@@ -767,6 +870,16 @@ macro_rules! eusci_b_impl {
                 self.$ucbxctlw1.write(UcbCtlw1_wr! {reg});
             }
 
+            #[inline(always)]
+            fn set_ucastp(&self, ucastp: Ucastp) {
+                self.$ucbxctlw1.modify(|_, w| w.ucastp().bits(ucastp as u8));
+            }
+
+            #[inline(always)]
+            fn ucbcnt_rd(&self) -> u8 {
+                self.$ucbxstatw().read().ucbcnt().bits()
+            }
+
             #[inline(always)]
             fn brw_rd(&self) -> u16 {
                 self.$ucbxbrw().read().bits()
@@ -889,6 +1002,16 @@ macro_rules! eusci_b_impl {
                 self.$ucbxie().write(UcbIe_wr! {reg});
             }
 
+            #[inline(always)]
+            fn bcntie_set(&self) {
+                unsafe { self.$ucbxie().set_bits(|w| w.ucbcntie().set_bit()) };
+            }
+
+            #[inline(always)]
+            fn bcntie_clear(&self) {
+                unsafe { self.$ucbxie().clear_bits(|w| w.ucbcntie().clear_bit()) };
+            }
+
             #[inline(always)]
             fn ifg_rd(&self) -> Self::IfgOut {
                 self.$ucbxifg().read()
@@ -899,6 +1022,11 @@ macro_rules! eusci_b_impl {
                 self.$ucbxifg().write(UcbIFG_wr! {reg});
             }
 
+            #[inline(always)]
+            fn alifg_nackifg_clr(&self) {
+                unsafe { self.$ucbxifg().clear_bits(|w| w.ucalifg().clear_bit().ucnackifg().clear_bit()) };
+            }
+
             #[inline(always)]
             fn iv_rd(&self) -> u16 {
                 self.$ucbxiv().read().bits()
@@ -906,6 +1034,11 @@ macro_rules! eusci_b_impl {
         }
 
         impl I2CUcbIfgOut for $Ifg {
+            #[inline(always)]
+            fn uccltoifg(&self) -> bool {
+                self.uccltoifg().bit()
+            }
+
             #[inline(always)]
             fn ucbcntifg(&self) -> bool {
                 self.ucbcntifg().bit()
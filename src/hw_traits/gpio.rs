@@ -34,6 +34,9 @@ pub trait GpioPeriph: Steal {
 }
 
 pub trait IntrPeriph: GpioPeriph {
+    // Index among the interrupt-capable ports, starting from 0
+    const INTR_IDX: usize;
+
     fn pxies_rd(&self) -> u8;
     fn pxies_wr(&self, bits: u8);
     fn pxies_set(&self, bits: u8);
@@ -79,7 +82,7 @@ macro_rules! reg_methods {
 macro_rules! gpio_impl {
     ($px:ident: $Px:ident =>
      $pxin:ident, $pxout:ident, $pxdir:ident, $pxren:ident, $pxselc:ident, $pxsel0:ident, $pxsel1:ident
-     $(, [$pxies:ident, $pxie:ident, $pxifg:ident, $pxiv:ident])?
+     $(, [$pxies:ident, $pxie:ident, $pxifg:ident, $pxiv:ident, $idx:literal])?
     ) => {
         mod $px {
             use super::*;
@@ -116,6 +119,8 @@ macro_rules! gpio_impl {
 
             $(
                 impl IntrPeriph for pac::$Px {
+                    const INTR_IDX: usize = $idx;
+
                     reg_methods!($pxies, pxies_rd, pxies_wr, pxies_set, pxies_clear);
                     reg_methods!($pxie, pxie_rd, pxie_wr, pxie_set, pxie_clear);
                     reg_methods!($pxifg, pxifg_rd, pxifg_wr, pxifg_set, pxifg_clear);
@@ -130,9 +135,9 @@ macro_rules! gpio_impl {
     };
 }
 
-gpio_impl!(p1: P1 => p1in, p1out, p1dir, p1ren, p1selc, p1sel0, p1sel1, [p1ies, p1ie, p1ifg, p1iv]);
-gpio_impl!(p2: P2 => p2in, p2out, p2dir, p2ren, p2selc, p2sel0, p2sel1, [p2ies, p2ie, p2ifg, p2iv]);
-gpio_impl!(p3: P3 => p3in, p3out, p3dir, p3ren, p3selc, p3sel0, p3sel1, [p3ies, p3ie, p3ifg, p3iv]);
-gpio_impl!(p4: P4 => p4in, p4out, p4dir, p4ren, p4selc, p4sel0, p4sel1, [p4ies, p4ie, p4ifg, p4iv]);
+gpio_impl!(p1: P1 => p1in, p1out, p1dir, p1ren, p1selc, p1sel0, p1sel1, [p1ies, p1ie, p1ifg, p1iv, 0]);
+gpio_impl!(p2: P2 => p2in, p2out, p2dir, p2ren, p2selc, p2sel0, p2sel1, [p2ies, p2ie, p2ifg, p2iv, 1]);
+gpio_impl!(p3: P3 => p3in, p3out, p3dir, p3ren, p3selc, p3sel0, p3sel1, [p3ies, p3ie, p3ifg, p3iv, 2]);
+gpio_impl!(p4: P4 => p4in, p4out, p4dir, p4ren, p4selc, p4sel0, p4sel1, [p4ies, p4ie, p4ifg, p4iv, 3]);
 gpio_impl!(p5: P5 => p5in, p5out, p5dir, p5ren, p5selc, p5sel0, p5sel1);
 gpio_impl!(p6: P6 => p6in, p6out, p6dir, p6ren, p6selc, p6sel0, p6sel1);
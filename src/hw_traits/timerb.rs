@@ -51,6 +51,22 @@ pub enum Outmod {
     ResetSet,
 }
 
+/// When a write to a compare register is loaded into its compare latch
+pub enum Clld {
+    /// Load immediately
+    Immediate,
+    /// Load when the counter reaches 0
+    OnZero,
+}
+
+/// How the compare latches of a timer are grouped
+pub enum Tbclgrp {
+    /// Each latch is loaded on its own
+    Individual = 0b00,
+    /// All latches, including TBxCL0, form one group whose load event is set by TBxCCR1
+    All = 0b11,
+}
+
 pub enum Cm {
     NoCap,
     RisingEdge,
@@ -73,6 +89,10 @@ pub trait TimerB: Steal {
     fn upmode(&self);
     /// Set to continuous mode, reset timer, and clear interrupts
     fn continuous(&self);
+    /// Set to up/down mode, reset timer, and clear interrupts
+    fn updown(&self);
+    /// Set a stopped timer to upmode without resetting the timer or clearing interrupts
+    fn resume_upmode(&self);
 
     /// Apply clock select settings
     fn config_clock(&self, tbssel: Tbssel, div: TimerDiv);
@@ -86,6 +106,10 @@ pub trait TimerB: Steal {
     /// Set expansion register clock divider settings
     fn set_tbidex(&self, tbidex: TimerExDiv);
 
+    /// Set compare latch grouping. Grouped latches are only loaded together, once all of them have
+    /// been written.
+    fn set_tbclgrp(&self, tbclgrp: Tbclgrp);
+
     fn tbifg_rd(&self) -> bool;
     fn tbifg_clr(&self);
 
@@ -93,6 +117,9 @@ pub trait TimerB: Steal {
     fn tbie_clr(&self);
 
     fn tbxiv_rd(&self) -> u16;
+
+    /// Read the current timer count
+    fn tbxr_rd(&self) -> u16;
 }
 
 pub trait CCRn<C>: Steal {
@@ -100,7 +127,12 @@ pub trait CCRn<C>: Steal {
     fn get_ccrn(&self) -> u16;
 
     fn config_outmod(&self, outmod: Outmod);
-    fn config_cap_mode(&self, cm: Cm, ccis: Ccis);
+    fn outmod_rd(&self) -> u8;
+    /// Set to output mode 0 and drive the output to the given level
+    fn config_out(&self, high: bool);
+    fn config_clld(&self, clld: Clld);
+    fn config_cap_mode(&self, cm: Cm, ccis: Ccis, scs: bool);
+    fn is_cap_mode(&self) -> bool;
 
     fn ccifg_rd(&self) -> bool;
     fn ccifg_clr(&self);
@@ -146,12 +178,31 @@ macro_rules! ccrn_impl {
             }
 
             #[inline(always)]
-            fn config_cap_mode(&self, cm: Cm, ccis: Ccis) {
+            fn outmod_rd(&self) -> u8 {
+                self.$tbxcctln.read().outmod().bits()
+            }
+
+            #[inline(always)]
+            fn config_out(&self, high: bool) {
+                self.$tbxcctln
+                    .write(|w| w.outmod().bits(Outmod::Out as u8).out().bit(high));
+            }
+
+            #[inline(always)]
+            fn config_clld(&self, clld: Clld) {
+                unsafe {
+                    self.$tbxcctln.clear_bits(|w| w.clld().bits(0));
+                    self.$tbxcctln.set_bits(|w| w.clld().bits(clld as u8));
+                }
+            }
+
+            #[inline(always)]
+            fn config_cap_mode(&self, cm: Cm, ccis: Ccis, scs: bool) {
                 self.$tbxcctln.write(|w| {
                     w.cap()
                         .capture()
                         .scs()
-                        .sync()
+                        .bit(scs)
                         .cm()
                         .bits(cm as u8)
                         .ccis()
@@ -159,6 +210,11 @@ macro_rules! ccrn_impl {
                 });
             }
 
+            #[inline(always)]
+            fn is_cap_mode(&self) -> bool {
+                self.$tbxcctln.read().cap().bit()
+            }
+
             #[inline(always)]
             fn ccifg_rd(&self) -> bool {
                 self.$tbxcctln.read().ccifg().bit()
@@ -197,7 +253,7 @@ macro_rules! ccrn_impl {
 }
 
 macro_rules! timerb_impl {
-    ($TBx:ident, $tbx:ident, $tbxctl:ident, $tbxex:ident, $tbxiv:ident, $([$CCRn:ident, $tbxcctln:ident, $tbxccrn:ident]),*) => {
+    ($TBx:ident, $tbx:ident, $tbxctl:ident, $tbxex:ident, $tbxiv:ident, $tbxr:ident, $([$CCRn:ident, $tbxcctln:ident, $tbxccrn:ident]),*) => {
         impl Steal for pac::$TBx {
             #[inline(always)]
             unsafe fn steal() -> Self {
@@ -237,6 +293,24 @@ macro_rules! timerb_impl {
                 });
             }
 
+            #[inline(always)]
+            fn updown(&self) {
+                self.$tbxctl.modify(|r, w| {
+                    unsafe { w.bits(r.bits()) }
+                        .tbclr()
+                        .set_bit()
+                        .tbifg()
+                        .clear_bit()
+                        .mc()
+                        .updown()
+                });
+            }
+
+            #[inline(always)]
+            fn resume_upmode(&self) {
+                unsafe { self.$tbxctl.set_bits(|w| w.mc().up()) };
+            }
+
             #[inline(always)]
             fn config_clock(&self, tbssel: Tbssel, div: TimerDiv) {
                 self.$tbxctl
@@ -258,6 +332,14 @@ macro_rules! timerb_impl {
                 self.$tbxex.write(|w| w.tbidex().bits(tbidex as u8));
             }
 
+            #[inline(always)]
+            fn set_tbclgrp(&self, tbclgrp: Tbclgrp) {
+                unsafe {
+                    self.$tbxctl.clear_bits(|w| w.tbclgrp().bits(0));
+                    self.$tbxctl.set_bits(|w| w.tbclgrp().bits(tbclgrp as u8));
+                }
+            }
+
             #[inline(always)]
             fn tbifg_rd(&self) -> bool {
                 self.$tbxctl.read().tbifg().bit()
@@ -282,6 +364,11 @@ macro_rules! timerb_impl {
             fn tbxiv_rd(&self) -> u16 {
                 self.$tbxiv.read().bits()
             }
+
+            #[inline(always)]
+            fn tbxr_rd(&self) -> u16 {
+                self.$tbxr.read().bits()
+            }
         }
 
         $(ccrn_impl!($TBx, $CCRn, $tbxcctln, $tbxccrn);)*
@@ -294,6 +381,7 @@ timerb_impl!(
     tb0ctl,
     tb0ex0,
     tb0iv,
+    tb0r,
     [CCR0, tb0cctl0, tb0ccr0],
     [CCR1, tb0cctl1, tb0ccr1],
     [CCR2, tb0cctl2, tb0ccr2]
@@ -305,6 +393,7 @@ timerb_impl!(
     tb1ctl,
     tb1ex0,
     tb1iv,
+    tb1r,
     [CCR0, tb1cctl0, tb1ccr0],
     [CCR1, tb1cctl1, tb1ccr1],
     [CCR2, tb1cctl2, tb1ccr2]
@@ -316,6 +405,7 @@ timerb_impl!(
     tb2ctl,
     tb2ex0,
     tb2iv,
+    tb2r,
     [CCR0, tb2cctl0, tb2ccr0],
     [CCR1, tb2cctl1, tb2ccr1],
     [CCR2, tb2cctl2, tb2ccr2]
@@ -327,6 +417,7 @@ timerb_impl!(
     tb3ctl,
     tb3ex0,
     tb3iv,
+    tb3r,
     [CCR0, tb3cctl0, tb3ccr0],
     [CCR1, tb3cctl1, tb3ccr1],
     [CCR2, tb3cctl2, tb3ccr2],
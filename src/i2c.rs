@@ -3,10 +3,29 @@
 //! Peripherals eUSCI_B0 and eUSCI_B1 can be used for I2C communication.
 //!
 //! Begin by calling `I2cBusConfig::new()`. Once configured an `I2cBus` will be returned.
+//! `I2CBusConfig::use_smclk_scl_freq()` picks the clock divisor and glitch filter for a target SCL frequency,
+//! up to 1MHz fast-mode plus.
 //! 
 //! `I2cBus` implements the blocking embedded_hal `Read`, `Write` and `WriteRead` traits. 
 //! Passing a `u8` address to these methods uses 7-bit addressing, passing a `u16` uses 10-bit addressing.
-//! 
+//! `WriteRead` separates the write and read with a repeated START. For devices that need a full STOP in between,
+//! use `I2cBus::write_stop_read()` instead.
+//!
+//! `I2cBus::is_slave_present()` checks whether a single 7-bit address responds, and `I2cBus::scan()`
+//! probes every non-reserved 7-bit address to find all devices on the bus.
+//!
+//! The hardware byte counter can be used to generate a STOP condition and/or an interrupt once a
+//! known number of bytes has been transferred. See `I2cBus::set_byte_count_target()`.
+//!
+//! `I2CBusConfig::clock_low_timeout()` makes blocking transactions fail with `I2CErr::ClockLowTimeout` instead of hanging
+//! when a slave holds SCL low indefinitely.
+//!
+//! If a slave was interrupted mid-byte and is holding SDA low, `I2CBusConfig::recover_bus()` clocks it out of that state
+//! before the pins are handed to the eUSCI.
+//!
+//! To share one bus between several device drivers, put the `I2cBus` in a `RefCell` and give each
+//! driver its own `I2cDevice` handle.
+//!
 //! Pins used:
 //!
 //! eUSCI_B0: {SCL: `P1.3`, SDA: `P1.2`}. `P1.1` can optionally be used as an external clock source.
@@ -14,13 +33,15 @@
 //! eUSCI_B1: {SCL: `P4.7`, SDA: `P4.6`}. `P4.5` can optionally be used as an external clock source.
 //!
 
-use crate::clock::{Aclk, Smclk};
-use crate::gpio::{Pin1, Pin5};
+use crate::clock::{Aclk, Clock, Smclk};
+use crate::gpio::{GpioFunction, Pin1, Pin5, PinNum, PortNum};
+use crate::hw_traits::gpio::GpioPeriph;
 use crate::hw_traits::eusci::I2CUcbIfgOut;
 use crate::{
     gpio::{Alternate1, Pin, Pin2, Pin3, Pin6, Pin7, P1, P4},
+    hal::blocking::delay::DelayUs,
     hal::blocking::i2c::{
-        Read, SevenBitAddress, TenBitAddress, Write, WriteRead,
+        AddressMode, Read, SevenBitAddress, TenBitAddress, Write, WriteRead,
     },
     hw_traits::eusci::{
         EUsciI2C, Ucastp, UcbCtlw0, UcbCtlw1, UcbI2coa, UcbIFG, UcbIe, Ucclto, Ucglit, Ucmode,
@@ -28,8 +49,8 @@ use crate::{
     },
     pac,
 };
+use core::cell::RefCell;
 use core::marker::PhantomData;
-use msp430::asm;
 
 /// Configure bus to use 7bit or 10bit I2C slave addressing mode
 #[derive(Clone, Copy)]
@@ -91,6 +112,30 @@ impl From<GlitchFilter> for Ucglit {
     }
 }
 
+/// How long SCL may be held low before the transaction is aborted, measured in MODCLK cycles
+#[derive(Clone, Copy)]
+pub enum ClockLowTimeout {
+    /// Never time out
+    Disabled,
+    /// 135000 MODCLK cycles (approximately 28 ms)
+    Cycles135000,
+    /// 150000 MODCLK cycles (approximately 31 ms)
+    Cycles150000,
+    /// 165000 MODCLK cycles (approximately 34 ms)
+    Cycles165000,
+}
+
+impl From<ClockLowTimeout> for Ucclto {
+    fn from(f: ClockLowTimeout) -> Ucclto {
+        match f {
+            ClockLowTimeout::Disabled => Ucclto::Ucclto00b,
+            ClockLowTimeout::Cycles135000 => Ucclto::Ucclto01b,
+            ClockLowTimeout::Cycles150000 => Ucclto::Ucclto10b,
+            ClockLowTimeout::Cycles165000 => Ucclto::Ucclto11b,
+        }
+    }
+}
+
 ///Struct used to configure a I2C bus
 pub struct I2CBusConfig<USCI: I2cUsci, STATE> {
     usci: USCI,
@@ -116,18 +161,30 @@ pub trait I2cUsci: EUsciI2C {
     type DataPin;
     /// I2C external clock source pin. Only necessary if UCLKI is selected as a clock source.
     type ExternalClockPin;
+    /// GPIO port of the SCL and SDA pins
+    type GpioPort: PortNum;
+    /// GPIO pin number of SCL
+    type SclNum: PinNum;
+    /// GPIO pin number of SDA
+    type SdaNum: PinNum;
 }
 
 impl I2cUsci for pac::E_USCI_B0 {
     type ClockPin = UsciB0SCLPin;
     type DataPin = UsciB0SDAPin;
     type ExternalClockPin = UsciB0UCLKIPin;
+    type GpioPort = P1;
+    type SclNum = Pin3;
+    type SdaNum = Pin2;
 }
 
 impl I2cUsci for pac::E_USCI_B1 {
     type ClockPin = UsciB1SCLPin;
     type DataPin = UsciB1SDAPin;
     type ExternalClockPin = UsciB1UCLKIPin;
+    type GpioPort = P4;
+    type SclNum = Pin7;
+    type SdaNum = Pin6;
 }
 
 // Allows a GPIO pin to be converted into an I2C object
@@ -329,6 +386,110 @@ impl<USCI: I2cUsci> I2CBusConfig<USCI, NoClockSet> {
             ifg: self.ifg, 
             _phantom: PhantomData }
     }
+
+    /// Configures this peripheral to use SMCLK, choosing the divisor so SCL runs as close to `scl_hz` as possible without exceeding it.
+    /// Rates up to 1MHz (fast-mode plus) are accepted.
+    /// 
+    /// Above 100kHz the glitch filter is set to 50ns, the spike suppression required by the I2C specification for fast-mode 
+    /// and fast-mode plus, overriding the filter passed to `new()`. Shorter filters let noise on the slower edges through as extra clock pulses.
+    /// 
+    /// At fast-mode plus the SCL and SDA rise times must stay below 120ns. The pins can only sink a few milliamps, 
+    /// so the pull-up resistors can't be made arbitrarily strong and the bus capacitance must be kept low, i.e. short traces and few devices. 
+    /// If the bus is unreliable at 1MHz, check the rise times on a scope before suspecting the firmware.
+    #[inline]
+    pub fn use_smclk_scl_freq(mut self, smclk: &Smclk, scl_hz: u32) -> Result<I2CBusConfig<USCI, ClockSet>, I2cClockErr> {
+        if scl_hz > 1_000_000 {
+            return Err(I2cClockErr::SclTooFast);
+        }
+        if scl_hz == 0 || smclk.freq() < scl_hz {
+            return Err(I2cClockErr::ClockTooSlow);
+        }
+        let divisor = smclk.freq().div_ceil(scl_hz);
+        if divisor > u16::MAX as u32 {
+            return Err(I2cClockErr::ClockTooFast);
+        }
+        if scl_hz > 100_000 {
+            self.ctlw1.ucglit = Ucglit::Max50ns;
+        }
+        Ok(self.use_smclk(smclk, divisor as u16))
+    }
+}
+
+impl<USCI: I2cUsci, STATE> I2CBusConfig<USCI, STATE> {
+    /// Abort blocking transactions with `I2CErr::ClockLowTimeout` if SCL is held low for longer than `timeout`,
+    /// such as by a slave that stretches the clock forever. Disabled by default.
+    #[inline]
+    pub fn clock_low_timeout(mut self, timeout: ClockLowTimeout) -> Self {
+        self.ctlw1.ucclto = timeout.into();
+        self
+    }
+
+    /// Free a bus left stuck by a slave that was interrupted mid-byte and is holding SDA low, e.g. after a reset of this device.
+    /// Call this before `configure()`, while SCL and SDA are still GPIO pins.
+    ///
+    /// If SDA is low, up to 9 clock pulses are sent on SCL until the slave releases it, followed by a STOP condition.
+    /// Both lines are driven open-drain at roughly 100kHz, relying on the bus pull-ups to pull them high.
+    /// SDA is released first, even if it was configured as an output.
+    /// The pins' direction and output registers are restored afterwards. Returns whether SDA is released,
+    /// returning immediately if it already was.
+    pub fn recover_bus<SCLDIR: GpioFunction, SDADIR: GpioFunction, D: DelayUs<u16>>(
+        &mut self,
+        _scl: &Pin<USCI::GpioPort, USCI::SclNum, SCLDIR>,
+        _sda: &Pin<USCI::GpioPort, USCI::SdaNum, SDADIR>,
+        delay: &mut D,
+    ) -> bool {
+        let port = unsafe { USCI::GpioPort::steal() };
+        let scl = USCI::SclNum::SET_MASK;
+        let sda = USCI::SdaNum::SET_MASK;
+        let sda_released = |port: &USCI::GpioPort| port.pxin_rd() & sda != 0;
+
+        if sda_released(&port) {
+            return true;
+        }
+
+        let dir = port.pxdir_rd();
+        let out = port.pxout_rd();
+        // A line is pulled low by driving a 0 and released by switching it back to an input
+        port.pxout_clear(!(scl | sda));
+        // SDA may have been an output, so release it before clocking or it stays driven low
+        port.pxdir_clear(!sda);
+        for _ in 0..9 {
+            port.pxdir_set(scl);
+            delay.delay_us(5);
+            port.pxdir_clear(!scl);
+            delay.delay_us(5);
+            if sda_released(&port) {
+                break;
+            }
+        }
+
+        // STOP condition: SDA rises while SCL is high
+        port.pxdir_set(scl);
+        delay.delay_us(5);
+        port.pxdir_set(sda);
+        delay.delay_us(5);
+        port.pxdir_clear(!scl);
+        delay.delay_us(5);
+        port.pxdir_clear(!sda);
+        delay.delay_us(5);
+
+        let released = sda_released(&port);
+        let mask = scl | sda;
+        port.pxout_wr((port.pxout_rd() & !mask) | (out & mask));
+        port.pxdir_wr((port.pxdir_rd() & !mask) | (dir & mask));
+        released
+    }
+}
+
+/// Errors when choosing an I2C clock divisor from a target SCL frequency
+#[derive(Clone, Copy, Debug)]
+pub enum I2cClockErr {
+    /// The requested SCL frequency is above 1MHz, the maximum supported by the eUSCI (fast-mode plus)
+    SclTooFast,
+    /// The clock source is slower than the requested SCL frequency
+    ClockTooSlow,
+    /// The clock source is so much faster than the requested SCL frequency that the divisor would overflow
+    ClockTooFast,
 }
 
 #[allow(private_bounds)]
@@ -340,7 +501,10 @@ impl<USCI: I2cUsci> I2CBusConfig<USCI, ClockSet> {
         _sda: D,
     ) -> I2cBus<USCI> {
         self.configure_regs();
-        I2cBus(PhantomData)
+        I2cBus {
+            last_error_flags: None,
+            _usci: PhantomData,
+        }
     }
 
     /// Performs hardware configuration
@@ -365,7 +529,47 @@ impl<USCI: I2cUsci> I2CBusConfig<USCI, ClockSet> {
 }
 
 /// I2C data bus
-pub struct I2cBus<USCI: I2cUsci>(PhantomData<USCI>);
+pub struct I2cBus<USCI: I2cUsci> {
+    last_error_flags: Option<I2cFlags>,
+    _usci: PhantomData<USCI>,
+}
+
+/// Snapshot of the I2C interrupt flags, taken at the moment a blocking transaction failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct I2cFlags {
+    /// Not-acknowledge received (UCNACKIFG)
+    pub nack: bool,
+    /// Arbitration lost (UCALIFG)
+    pub arbitration_lost: bool,
+    /// STOP condition detected (UCSTPIFG)
+    pub stop: bool,
+    /// START condition detected (UCSTTIFG)
+    pub start: bool,
+    /// Byte counter threshold reached (UCBCNTIFG)
+    pub byte_count: bool,
+    /// Clock low timeout expired (UCCLTOIFG)
+    pub clock_low_timeout: bool,
+    /// Transmit buffer empty (UCTXIFG0)
+    pub tx_empty: bool,
+    /// Receive buffer full (UCRXIFG0)
+    pub rx_full: bool,
+}
+
+impl I2cFlags {
+    #[inline(always)]
+    fn from_ifg<IFG: I2CUcbIfgOut>(ifg: &IFG) -> Self {
+        I2cFlags {
+            nack: ifg.ucnackifg(),
+            arbitration_lost: ifg.ucalifg(),
+            stop: ifg.ucstpifg(),
+            start: ifg.ucsttifg(),
+            byte_count: ifg.ucbcntifg(),
+            clock_low_timeout: ifg.uccltoifg(),
+            tx_empty: ifg.uctxifg0(),
+            rx_full: ifg.ucrxifg0(),
+        }
+    }
+}
 
 /// I2C transmit/receive errors
 #[derive(Clone, Copy, Debug)]
@@ -375,7 +579,9 @@ pub enum I2CErr {
     GotNACK,
     /// Device lost arbitration
     ArbitrationLost,
-    // Other errors such as the 'clock low timeout' UCCLTOIFG may appear here in future.
+    /// SCL was held low for longer than the timeout set by `I2CBusConfig::clock_low_timeout()`.
+    /// The eUSCI is reset to release the bus, which clears all I2C interrupt enables.
+    ClockLowTimeout,
 }
 
 impl<USCI: I2cUsci> I2cBus<USCI> {
@@ -391,6 +597,34 @@ impl<USCI: I2cUsci> I2cBus<USCI> {
         usci.set_uctr(mode.into())
     }
 
+    #[inline(always)]
+    fn record_error_flags<IFG: I2CUcbIfgOut>(&mut self, ifg: &IFG) {
+        self.last_error_flags = Some(I2cFlags::from_ifg(ifg));
+    }
+
+    /// Interrupt flags captured when the most recent failed transaction returned its error, for
+    /// telling apart the causes of intermittent bus faults. Returns `None` if no transaction has
+    /// failed yet. The snapshot is kept until the next failure.
+    #[inline(always)]
+    pub fn last_error_flags(&self) -> Option<I2cFlags> {
+        self.last_error_flags
+    }
+
+    /// If SCL has been held low for longer than the clock low timeout, reset the eUSCI to release
+    /// the bus and return an error. The flag is never raised while the timeout is disabled.
+    #[inline(always)]
+    fn check_clock_low_timeout(&mut self, usci: &USCI) -> Result<(), I2CErr> {
+        let ifg = usci.ifg_rd();
+        if ifg.uccltoifg() {
+            self.record_error_flags(&ifg);
+            // Resetting the eUSCI releases SCL and clears the timeout flag
+            usci.ctw0_set_rst();
+            usci.ctw0_clear_rst();
+            return Err::<(), I2CErr>(I2CErr::ClockLowTimeout);
+        }
+        Ok(())
+    }
+
     /// Blocking read
     fn read(&mut self, address: u16, buffer: &mut [u8]) -> Result<(), I2CErr> {
         if buffer.is_empty() { return Ok(()) }
@@ -401,14 +635,15 @@ impl<USCI: I2cUsci> I2cBus<USCI> {
         usci.transmit_start();
 
         while usci.uctxstt_rd() {
-            asm::nop();
+            self.check_clock_low_timeout(&usci)?;
         }
 
         let mut ifg = usci.ifg_rd();
         if ifg.ucnackifg() {
+            self.record_error_flags(&ifg);
             usci.transmit_stop();
             while usci.uctxstp_rd() {
-                asm::nop();
+                self.check_clock_low_timeout(&usci)?;
             }
             return Err::<(), I2CErr>(I2CErr::GotNACK);
         }
@@ -419,20 +654,22 @@ impl<USCI: I2cUsci> I2cBus<USCI> {
                 usci.transmit_stop();
             }
             while !ifg.ucrxifg0() {
+                self.check_clock_low_timeout(&usci)?;
                 ifg = usci.ifg_rd();
             }
             *byte = usci.ucrxbuf_rd();
         }
 
         while usci.uctxstp_rd() {
-            asm::nop();
+            self.check_clock_low_timeout(&usci)?;
         }
 
         Ok(())
     }
 
-    /// Blocking write
-    fn write(&mut self, address: u16, bytes: &[u8]) -> Result<(), I2CErr> {
+    /// Blocking write. Without `send_stop` the bus is left ready for a repeated START as soon as the last byte
+    /// starts being shifted out.
+    fn write(&mut self, address: u16, bytes: &[u8], send_stop: bool) -> Result<(), I2CErr> {
         if bytes.is_empty() { return Ok(()) }
         let usci = unsafe { USCI::steal() };
 
@@ -441,18 +678,20 @@ impl<USCI: I2cUsci> I2cBus<USCI> {
 
         let mut ifg = usci.ifg_rd();
         while !ifg.uctxifg0() {
+            self.check_clock_low_timeout(&usci)?;
             ifg = usci.ifg_rd();
         }
 
         while usci.uctxstt_rd() {
-            asm::nop();
+            self.check_clock_low_timeout(&usci)?;
         }
 
         ifg = usci.ifg_rd();
         if ifg.ucnackifg() {
+            self.record_error_flags(&ifg);
             usci.transmit_stop();
             while usci.uctxstp_rd() {
-                asm::nop();
+                self.check_clock_low_timeout(&usci)?;
             }
             return Err::<(), I2CErr>(I2CErr::GotNACK);
         }
@@ -461,31 +700,194 @@ impl<USCI: I2cUsci> I2cBus<USCI> {
             usci.uctxbuf_wr(byte);
             ifg = usci.ifg_rd();
             while !ifg.uctxifg0() {
+                self.check_clock_low_timeout(&usci)?;
                 ifg = usci.ifg_rd();
             }
             if ifg.ucnackifg() {
+                self.record_error_flags(&ifg);
                 usci.transmit_stop();
                 while usci.uctxstp_rd() {
-                    asm::nop();
+                    self.check_clock_low_timeout(&usci)?;
                 }
                 return Err::<(), I2CErr>(I2CErr::GotNACK);
             }
         }
+        if !send_stop {
+            return Ok(());
+        }
         // usci.uctxbuf_wr(bytes[bytes.len()-1]);
         usci.transmit_stop();
         while usci.uctxstp_rd() {
-            asm::nop();
+            self.check_clock_low_timeout(&usci)?;
+        }
+
+        // The last byte is only acknowledged after the loop above has finished
+        ifg = usci.ifg_rd();
+        if ifg.ucnackifg() {
+            self.record_error_flags(&ifg);
+            return Err::<(), I2CErr>(I2CErr::GotNACK);
         }
 
         Ok(())
     }
 
-    /// blocking write then blocking read
+    /// Send a START followed immediately by a STOP and report whether the address was acknowledged
+    fn probe(&mut self, address: u16) -> Result<bool, I2CErr> {
+        let usci = unsafe { USCI::steal() };
+
+        // Flags left over from an earlier transaction would be mistaken for this probe's result
+        usci.alifg_nackifg_clr();
+        usci.i2csa_wr(address);
+        usci.transmit_start();
+
+        while usci.uctxstt_rd() {
+            self.check_clock_low_timeout(&usci)?;
+        }
+
+        let ifg = usci.ifg_rd();
+        if ifg.ucalifg() {
+            return Err(self.restore_master_mode(&usci, &ifg));
+        }
+
+        usci.transmit_stop();
+        while usci.uctxstp_rd() {
+            self.check_clock_low_timeout(&usci)?;
+        }
+
+        let ifg = usci.ifg_rd();
+        if ifg.ucalifg() {
+            return Err(self.restore_master_mode(&usci, &ifg));
+        }
+        Ok(!ifg.ucnackifg())
+    }
+
+    /// Losing arbitration switches the eUSCI into slave mode. Switch back to master mode so later transactions can
+    /// still start, which briefly resets the peripheral and so clears all interrupt enables and flags.
+    #[inline(always)]
+    fn restore_master_mode<IFG: I2CUcbIfgOut>(&mut self, usci: &USCI, ifg: &IFG) -> I2CErr {
+        self.record_error_flags(ifg);
+        usci.ctw0_set_rst();
+        usci.set_ucmst(true);
+        usci.ctw0_clear_rst();
+        I2CErr::ArbitrationLost
+    }
+
+    /// Check whether a slave device acknowledges the given 7-bit address by performing a zero-byte write.
+    ///
+    /// Returns `I2CErr::ArbitrationLost` if another master took over the bus, after putting this device back into
+    /// master mode. Multi-master buses aren't otherwise supported yet, so the probe isn't retried.
+    pub fn is_slave_present(&mut self, address: u8) -> Result<bool, I2CErr> {
+        self.set_addressing_mode(AddressingMode::SevenBit);
+        self.set_transmission_mode(TransmissionMode::Transmit);
+        self.probe(address as u16)
+    }
+
+    /// Probe every non-reserved 7-bit address (`0x08` to `0x77`) and write the addresses that
+    /// acknowledged into `out`, in ascending order. Returns the number of addresses written.
+    ///
+    /// The scan stops early once `out` is full, so a 112 byte buffer is enough to hold every
+    /// possible device.
+    ///
+    /// As with `is_slave_present()`, losing arbitration to another master ends the scan with
+    /// `I2CErr::ArbitrationLost`.
+    pub fn scan(&mut self, out: &mut [u8]) -> Result<usize, I2CErr> {
+        self.set_addressing_mode(AddressingMode::SevenBit);
+        self.set_transmission_mode(TransmissionMode::Transmit);
+
+        let mut found = 0;
+        for address in 0x08..=0x77_u8 {
+            if found == out.len() {
+                break;
+            }
+            if self.probe(address as u16)? {
+                out[found] = address;
+                found += 1;
+            }
+        }
+        Ok(found)
+    }
+
+    /// Number of bytes transferred since the last START or repeated START condition.
+    /// The hardware counter is 8 bits wide, so this wraps after 255 bytes.
+    #[inline]
+    pub fn byte_count(&self) -> u8 {
+        let usci = unsafe { USCI::steal() };
+        usci.ucbcnt_rd()
+    }
+
+    /// Set the number of bytes after which the byte counter flag is raised. The hardware
+    /// threshold is 8 bits wide, so at most 255 bytes can be counted. If `auto_stop` is
+    /// true, a STOP condition is also generated automatically once `count` bytes have been
+    /// transferred, so a master can read a fixed-length block without manually issuing a STOP.
+    ///
+    /// Changing the target briefly resets the peripheral, which clears all interrupt enables and
+    /// flags, so call this before enabling any I2C interrupts. The blocking `Read`, `Write` and
+    /// `WriteRead` implementations issue their own STOP conditions, so auto-STOP should be turned
+    /// off with `disable_byte_counter()` before using them.
+    #[inline]
+    pub fn set_byte_count_target(&mut self, count: u8, auto_stop: bool) {
+        let usci = unsafe { USCI::steal() };
+        usci.ctw0_set_rst();
+        usci.set_ucastp(if auto_stop {
+            Ucastp::Ucastp10b
+        } else {
+            Ucastp::Ucastp01b
+        });
+        usci.tbcnt_wr(count.into());
+        usci.ctw0_clear_rst();
+    }
+
+    /// Disable the byte counter threshold and automatic STOP generation. Like
+    /// `set_byte_count_target()`, this clears all interrupt enables and flags.
+    #[inline]
+    pub fn disable_byte_counter(&mut self) {
+        let usci = unsafe { USCI::steal() };
+        usci.ctw0_set_rst();
+        usci.set_ucastp(Ucastp::Ucastp00b);
+        usci.tbcnt_wr(0);
+        usci.ctw0_clear_rst();
+    }
+
+    /// Whether the byte counter has reached the target set by `set_byte_count_target()`
+    #[inline]
+    pub fn byte_count_reached(&self) -> bool {
+        let usci = unsafe { USCI::steal() };
+        usci.ifg_rd().ucbcntifg()
+    }
+
+    /// Enable the byte counter interrupt, which fires once the target set by
+    /// `set_byte_count_target()` is reached
+    #[inline]
+    pub fn enable_byte_count_interrupt(&mut self) {
+        let usci = unsafe { USCI::steal() };
+        usci.bcntie_set();
+    }
+
+    /// Disable the byte counter interrupt
+    #[inline]
+    pub fn disable_byte_count_interrupt(&mut self) {
+        let usci = unsafe { USCI::steal() };
+        usci.bcntie_clear();
+    }
+
+    /// blocking write then blocking read, separated by a repeated START
     fn write_read(&mut self, address: u16, bytes: &[u8], buffer: &mut [u8]) -> Result<(), I2CErr> {
         self.set_transmission_mode(TransmissionMode::Transmit);
-        self.read(address, buffer)?;
+        self.write(address, bytes, buffer.is_empty())?;
+        self.set_transmission_mode(TransmissionMode::Receive);
+        self.read(address, buffer)
+    }
+
+    /// Like `write_read()`, but with a full STOP between the write and the read instead of a repeated START, 
+    /// for devices that don't support repeated STARTs. Uses 7-bit addressing.
+    /// 
+    /// A NACK at any point of the write, including the last byte, aborts the transaction before the read begins.
+    pub fn write_stop_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), I2CErr> {
+        self.set_addressing_mode(AddressingMode::SevenBit);
+        self.set_transmission_mode(TransmissionMode::Transmit);
+        self.write(address as u16, bytes, true)?;
         self.set_transmission_mode(TransmissionMode::Receive);
-        self.write(address, bytes)
+        self.read(address as u16, buffer)
     }
 }
 
@@ -512,7 +914,7 @@ impl<USCI: I2cUsci> Write<SevenBitAddress> for I2cBus<USCI> {
     fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
         self.set_addressing_mode(AddressingMode::SevenBit);
         self.set_transmission_mode(TransmissionMode::Transmit);
-        I2cBus::write(self, address as u16, bytes)
+        I2cBus::write(self, address as u16, bytes, true)
     }
 }
 
@@ -521,7 +923,7 @@ impl<USCI: I2cUsci> Write<TenBitAddress> for I2cBus<USCI> {
     fn write(&mut self, address: u16, bytes: &[u8]) -> Result<(), Self::Error> {
         self.set_addressing_mode(AddressingMode::TenBit);
         self.set_transmission_mode(TransmissionMode::Transmit);
-        I2cBus::write(self, address, bytes)
+        I2cBus::write(self, address, bytes, true)
     }
 }
 
@@ -550,3 +952,55 @@ impl<USCI: I2cUsci> WriteRead<TenBitAddress> for I2cBus<USCI> {
         I2cBus::write_read(self, address, bytes, buffer)
     }
 }
+
+/// Handle to an I2C bus shared between several device drivers.
+///
+/// Each driver gets its own handle to the same `RefCell<I2cBus>`, and the bus is borrowed for the
+/// duration of each transaction. Using a handle while another transaction on the same bus is in
+/// progress, such as from an interrupt, panics.
+pub struct I2cDevice<'a, USCI: I2cUsci> {
+    bus: &'a RefCell<I2cBus<USCI>>,
+}
+
+impl<'a, USCI: I2cUsci> I2cDevice<'a, USCI> {
+    /// Create a new handle to a shared I2C bus
+    #[inline(always)]
+    pub fn new(bus: &'a RefCell<I2cBus<USCI>>) -> Self {
+        Self { bus }
+    }
+}
+
+impl<'a, USCI: I2cUsci, A: AddressMode> Read<A> for I2cDevice<'a, USCI>
+where
+    I2cBus<USCI>: Read<A, Error = I2CErr>,
+{
+    type Error = I2CErr;
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        Read::read(&mut *self.bus.borrow_mut(), address, buffer)
+    }
+}
+
+impl<'a, USCI: I2cUsci, A: AddressMode> Write<A> for I2cDevice<'a, USCI>
+where
+    I2cBus<USCI>: Write<A, Error = I2CErr>,
+{
+    type Error = I2CErr;
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        Write::write(&mut *self.bus.borrow_mut(), address, bytes)
+    }
+}
+
+impl<'a, USCI: I2cUsci, A: AddressMode> WriteRead<A> for I2cDevice<'a, USCI>
+where
+    I2cBus<USCI>: WriteRead<A, Error = I2CErr>,
+{
+    type Error = I2CErr;
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        WriteRead::write_read(&mut *self.bus.borrow_mut(), address, bytes, buffer)
+    }
+}
@@ -34,12 +34,14 @@ pub mod capture;
 pub mod clock;
 pub mod fram;
 pub mod gpio;
+pub mod lpm;
 pub mod pmm;
 pub mod prelude;
 pub mod pwm;
 pub mod rtc;
 pub mod serial;
 pub mod timer;
+pub mod tlv;
 pub mod watchdog;
 
 mod hw_traits;
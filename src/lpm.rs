@@ -0,0 +1,114 @@
+//! Low power modes
+//!
+//! Entering a low power mode turns off the CPU along with some of the system clocks. Peripherals
+//! driven by a clock that gets turned off stop running, so anything still in progress when the
+//! device goes to sleep, such as a UART transmission clocked from SMCLK, will stall or be
+//! corrupted. Use `clocks_available_in()` to check which clocks keep running in each mode, and
+//! make sure peripherals depending on the other clocks have finished their work (e.g. with
+//! `Tx::wait_tx_complete()`) before going to sleep.
+//!
+//! Only an enabled interrupt can wake the CPU, so the entry functions also enable interrupts. The
+//! low power mode bits live in the status register, which is restored when the ISR returns, so
+//! the device goes back to sleep after servicing the interrupt. Work that needs to happen on
+//! wakeup should therefore be done in the ISR itself.
+//!
+//! GPIO pins on ports 1 to 4 can wake the CPU from any of these modes with
+//! `Pin::enable_wakeup()`. See the `gpio_wakeup` example.
+
+use core::arch::asm;
+
+// Status register bits
+const GIE: u16 = 1 << 3;
+const CPUOFF: u16 = 1 << 4;
+const OSCOFF: u16 = 1 << 5;
+const SCG0: u16 = 1 << 6;
+const SCG1: u16 = 1 << 7;
+
+/// Low power modes of the device
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LowPowerMode {
+    /// CPU and MCLK are off. SMCLK and ACLK keep running.
+    Lpm0,
+    /// CPU, MCLK, SMCLK and the FLL are off. ACLK keeps running.
+    Lpm3,
+    /// CPU and all clocks are off.
+    Lpm4,
+}
+
+/// Which system clocks keep running in a low power mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClocksAvailable {
+    /// MCLK, which drives the CPU
+    pub mclk: bool,
+    /// SMCLK, if it was enabled with `ClockConfig::smclk_on()`
+    pub smclk: bool,
+    /// ACLK
+    pub aclk: bool,
+}
+
+/// Which system clocks keep running in the given low power mode. Peripherals clocked by a clock
+/// that doesn't keep running must be idle before entering that mode.
+#[inline]
+pub const fn clocks_available_in(mode: LowPowerMode) -> ClocksAvailable {
+    match mode {
+        LowPowerMode::Lpm0 => ClocksAvailable {
+            mclk: false,
+            smclk: true,
+            aclk: true,
+        },
+        LowPowerMode::Lpm3 => ClocksAvailable {
+            mclk: false,
+            smclk: false,
+            aclk: true,
+        },
+        LowPowerMode::Lpm4 => ClocksAvailable {
+            mclk: false,
+            smclk: false,
+            aclk: false,
+        },
+    }
+}
+
+/// Enable interrupts and enter LPM0
+#[inline(always)]
+pub fn enter_lpm0() {
+    unsafe {
+        asm!("bis.w #{bits}, SR", "nop", bits = const GIE | CPUOFF, options(nostack));
+    }
+}
+
+/// Enable interrupts and enter LPM3
+#[inline(always)]
+pub fn enter_lpm3() {
+    unsafe {
+        asm!(
+            "bis.w #{bits}, SR",
+            "nop",
+            bits = const GIE | CPUOFF | SCG0 | SCG1,
+            options(nostack)
+        );
+    }
+}
+
+/// Enable interrupts and enter LPM4
+#[inline(always)]
+pub fn enter_lpm4() {
+    unsafe {
+        asm!(
+            "bis.w #{bits}, SR",
+            "nop",
+            bits = const GIE | CPUOFF | OSCOFF | SCG0 | SCG1,
+            options(nostack)
+        );
+    }
+}
+
+/// Enable interrupts and enter the given low power mode
+#[inline(always)]
+pub fn enter(mode: LowPowerMode) {
+    match mode {
+        LowPowerMode::Lpm0 => enter_lpm0(),
+        LowPowerMode::Lpm3 => enter_lpm3(),
+        LowPowerMode::Lpm4 => enter_lpm4(),
+    }
+}
@@ -1,14 +1,201 @@
 //! Power management module
+//!
+//! Creating a `Pmm` unlocks the GPIO pins by clearing LOCKLPM5, so `&Pmm` is used throughout the
+//! HAL as proof that this has happened.
+//!
+//! The high-side supply voltage supervisor (SVSH) can additionally be enabled with
+//! `Pmm::enable_svs()`, which returns an `SvsOn` token. Code that must not run on a brown-out
+//! supply, such as FRAM writers, can require `&SvsOn` to make that dependency explicit.
+//!
+//! The `Pmm` also owns the shared internal voltage reference, which is used by the ADC and the
+//! temperature sensor. `Pmm::acquire_internal_ref()` hands out `InternalVRef` tokens as long as
+//! every user agrees on the reference voltage, so one peripheral can't change the voltage out from
+//! under another. The reference is turned off once every token has been released.
 
 use msp430fr2355::PMM;
 
+const PASSWORD: u8 = 0xA5;
+// How many times to poll REFGENRDY before giving up on the reference
+const REF_READY_POLLS: u32 = 100_000;
+
 /// PMM type
-pub struct Pmm(());
+pub struct Pmm {
+    periph: PMM,
+    ref_voltage: Option<RefVoltage>,
+    ref_users: u8,
+}
+
+/// Voltage of the internal shared reference
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefVoltage {
+    /// 1.5V
+    _1V5,
+    /// 2.0V
+    _2V0,
+    /// 2.5V
+    _2V5,
+}
+
+impl RefVoltage {
+    #[inline(always)]
+    fn refvsel(self) -> u8 {
+        match self {
+            RefVoltage::_1V5 => 0b00,
+            RefVoltage::_2V0 => 0b01,
+            RefVoltage::_2V5 => 0b10,
+        }
+    }
+
+    /// The reference voltage in millivolts
+    #[inline]
+    pub fn millivolts(self) -> u16 {
+        match self {
+            RefVoltage::_1V5 => 1500,
+            RefVoltage::_2V0 => 2000,
+            RefVoltage::_2V5 => 2500,
+        }
+    }
+}
+
+/// Errors when acquiring the internal shared reference
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefErr {
+    /// The reference is already in use at the contained voltage, which differs from the one
+    /// requested
+    VoltageConflict(RefVoltage),
+    /// The reference generator didn't report ready in time. The reference has been turned off
+    /// again.
+    NotReady,
+}
+
+/// Token proving that the internal shared reference is on at a fixed voltage. The voltage can't
+/// change while any token is alive.
+pub struct InternalVRef {
+    voltage: RefVoltage,
+}
+
+impl InternalVRef {
+    /// Voltage of the reference
+    #[inline]
+    pub fn voltage(&self) -> RefVoltage {
+        self.voltage
+    }
+}
+
+/// Token proving that the temperature sensor is on. The sensor's calibration values in the TLV
+/// are measured against the 1.5V reference, so the sensor holds the reference at that voltage.
+pub struct TempSensor {
+    vref: InternalVRef,
+}
+
+impl TempSensor {
+    /// The 1.5V reference held by the temperature sensor
+    #[inline]
+    pub fn vref(&self) -> &InternalVRef {
+        &self.vref
+    }
+}
+
+/// Token proving that the high-side supply voltage supervisor is enabled, so the device is held
+/// in reset whenever the supply drops below the SVSH threshold
+pub struct SvsOn(());
 
 impl Pmm {
     /// Sets the LOCKLPM5 bit and returns a `Pmm`
     pub fn new(pmm: PMM) -> Pmm {
         pmm.pm5ctl0.write(|w| w.locklpm5().locklpm5_0());
-        Pmm(())
+        Pmm {
+            periph: pmm,
+            ref_voltage: None,
+            ref_users: 0,
+        }
+    }
+
+    // Run `f` with the PMM registers unlocked, locking them again afterwards
+    #[inline(always)]
+    fn unlocked<R>(&self, f: impl FnOnce(&PMM) -> R) -> R {
+        self.periph
+            .pmmctl0
+            .modify(|_, w| unsafe { w.pmmpw().bits(PASSWORD) });
+        let res = f(&self.periph);
+        // Writing anything other than the password to the upper byte of PMMCTL0 (the first
+        // register in the block) locks the registers again
+        unsafe { core::ptr::write_volatile((PMM::ptr() as *mut u8).add(1), 0) };
+        res
+    }
+
+    /// Turn on the internal shared reference at `voltage`, or share it if it's already on at the
+    /// same voltage. Fails if it's already in use at a different voltage, or if the reference
+    /// generator doesn't become ready.
+    pub fn acquire_internal_ref(&mut self, voltage: RefVoltage) -> Result<InternalVRef, RefErr> {
+        match self.ref_voltage {
+            Some(current) if current != voltage => return Err(RefErr::VoltageConflict(current)),
+            Some(_) => (),
+            None => {
+                self.unlocked(|pmm| {
+                    pmm.pmmctl2.modify(|_, w| {
+                        unsafe { w.refvsel().bits(voltage.refvsel()) }
+                            .intrefen()
+                            .set_bit()
+                    })
+                });
+                let ready = (0..REF_READY_POLLS)
+                    .any(|_| self.periph.pmmctl2.read().refgenrdy().bit_is_set());
+                if !ready {
+                    self.unlocked(|pmm| unsafe {
+                        pmm.pmmctl2.clear_bits(|w| w.intrefen().clear_bit())
+                    });
+                    return Err(RefErr::NotReady);
+                }
+                self.ref_voltage = Some(voltage);
+            }
+        }
+        self.ref_users += 1;
+        Ok(InternalVRef { voltage })
+    }
+
+    /// Give back an internal reference token. The reference is turned off once every token has
+    /// been released.
+    pub fn release_internal_ref(&mut self, _vref: InternalVRef) {
+        self.ref_users -= 1;
+        if self.ref_users == 0 {
+            self.ref_voltage = None;
+            self.unlocked(|pmm| unsafe { pmm.pmmctl2.clear_bits(|w| w.intrefen().clear_bit()) });
+        }
+    }
+
+    /// Turn on the temperature sensor, which also holds the internal reference at 1.5V. Fails if
+    /// the reference is already in use at a different voltage.
+    pub fn enable_temp_sensor(&mut self) -> Result<TempSensor, RefErr> {
+        let vref = self.acquire_internal_ref(RefVoltage::_1V5)?;
+        self.unlocked(|pmm| unsafe { pmm.pmmctl2.set_bits(|w| w.tsensoren().set_bit()) });
+        Ok(TempSensor { vref })
+    }
+
+    /// Turn off the temperature sensor and release its hold on the internal reference
+    pub fn disable_temp_sensor(&mut self, sensor: TempSensor) {
+        self.unlocked(|pmm| unsafe { pmm.pmmctl2.clear_bits(|w| w.tsensoren().clear_bit()) });
+        self.release_internal_ref(sensor.vref);
+    }
+
+    /// Enable the high-side supply voltage supervisor and return a token proving it's enabled
+    #[inline]
+    pub fn enable_svs(&mut self) -> SvsOn {
+        // Word writes to PMMCTL0 must carry the password as well
+        self.unlocked(|pmm| {
+            pmm.pmmctl0
+                .modify(|_, w| unsafe { w.pmmpw().bits(PASSWORD) }.svshe().set_bit())
+        });
+        SvsOn(())
+    }
+
+    /// Disable the high-side supply voltage supervisor to save power, consuming the token that
+    /// proved it was enabled
+    #[inline]
+    pub fn disable_svs(&mut self, _svs: SvsOn) {
+        self.unlocked(|pmm| {
+            pmm.pmmctl0
+                .modify(|_, w| unsafe { w.pmmpw().bits(PASSWORD) }.svshe().clear_bit())
+        });
     }
 }
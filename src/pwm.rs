@@ -4,13 +4,37 @@
 //! pins which all share the same period but have their own duty cycles.
 //!
 //! Each PWM pin starts off in an "uninitialized" state and must be initialized by passing in the
-//! appropriate alternate-function GPIO pin. Only initialized pins can be used for PWM.
+//! appropriate alternate-function GPIO pin. Only initialized pins can be used for PWM. Pins that
+//! connect to a timer output have an `into_pwm_output()` method which performs the correct
+//! alternate-function conversion for that pin.
+//!
+//! PWM pins are active-high by default, meaning the output is high for `duty` cycles of each
+//! period. Use `Pwm::set_polarity()` to invert the output of an individual pin, which is useful for
+//! driving active-low loads such as common-anode LEDs.
+//!
+//! By default a new duty cycle takes effect as soon as it's written, even in the middle of a
+//! period. Pins put into latched mode with `Pwm::set_latched()` only pick up new duty cycles at the
+//! start of the next period. To switch several pins of the same timer to their new duty cycles in
+//! the same period, which matters when driving things like motor phases or RGB LEDs, make the
+//! changes inside `PwmPeriod::update_synchronized()`.
+//!
+//! The period can be changed at runtime through the `period` handle of the PWM parts, which also
+//! keeps the duty cycles of the pins within the new period.
+//!
+//! `PwmComplementary` drives a pair of inverted outputs with a dead time between them, for
+//! half-bridges that must never have both switches on at once.
+//!
+//! Capture-compare registers that aren't needed for PWM can be repurposed as input captures with
+//! `PwmUninit::into_capture_input_a()` or `PwmUninit::into_capture_input_b()`, allowing a single
+//! timer to both drive PWM outputs and time external signals. Since the timer runs in up mode,
+//! captured values are between 0 and the PWM period.
 
+use crate::capture::{CapTrigger, Capture};
 use crate::gpio::{
-    Alternate1, Alternate2, ChangeSelectBits, Output, Pin, Pin0, Pin1, Pin2, Pin3, Pin4, Pin5,
-    Pin6, Pin7, P1, P2, P5, P6,
+    Alternate1, Alternate2, ChangeSelectBits, Floating, Input, Output, Pin, Pin0, Pin1, Pin2, Pin3,
+    Pin4, Pin5, Pin6, Pin7, P1, P2, P5, P6,
 };
-use crate::hw_traits::timerb::{CCRn, Outmod};
+use crate::hw_traits::timerb::{CCRn, Ccis, Clld, Outmod, Tbclgrp};
 use crate::timer::{CapCmpTimer3, CapCmpTimer7};
 use core::marker::PhantomData;
 use embedded_hal::PwmPin;
@@ -32,6 +56,8 @@ pub enum Alt {
 pub trait PwmPeriph<C>: CapCmp<C> + CapCmp<CCR0> {
     /// GPIO type
     type Gpio: ChangeSelectBits;
+    /// GPIO type used as capture input A when the channel is used for capture instead of PWM
+    type CaptureGpio;
     #[doc(hidden)]
     const ALT: Alt;
 
@@ -54,56 +80,103 @@ pub trait PwmPeriph<C>: CapCmp<C> + CapCmp<CCR0> {
 
 impl PwmPeriph<CCR1> for pac::TB0 {
     type Gpio = Pin<P1, Pin6, Alternate2<Output>>;
+    type CaptureGpio = Pin<P1, Pin6, Alternate2<Input<Floating>>>;
     const ALT: Alt = Alt::Alt2;
 }
 impl PwmPeriph<CCR2> for pac::TB0 {
     type Gpio = Pin<P1, Pin7, Alternate2<Output>>;
+    type CaptureGpio = Pin<P1, Pin7, Alternate2<Input<Floating>>>;
     const ALT: Alt = Alt::Alt2;
 }
 
 impl PwmPeriph<CCR1> for pac::TB1 {
     type Gpio = Pin<P2, Pin0, Alternate1<Output>>;
+    type CaptureGpio = Pin<P2, Pin0, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 impl PwmPeriph<CCR2> for pac::TB1 {
     type Gpio = Pin<P2, Pin1, Alternate1<Output>>;
+    type CaptureGpio = Pin<P2, Pin1, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 
 impl PwmPeriph<CCR1> for pac::TB2 {
     type Gpio = Pin<P5, Pin0, Alternate1<Output>>;
+    type CaptureGpio = Pin<P5, Pin0, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 impl PwmPeriph<CCR2> for pac::TB2 {
     type Gpio = Pin<P5, Pin1, Alternate1<Output>>;
+    type CaptureGpio = Pin<P5, Pin1, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 
 impl PwmPeriph<CCR1> for pac::TB3 {
     type Gpio = Pin<P6, Pin0, Alternate1<Output>>;
+    type CaptureGpio = Pin<P6, Pin0, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 impl PwmPeriph<CCR2> for pac::TB3 {
     type Gpio = Pin<P6, Pin1, Alternate1<Output>>;
+    type CaptureGpio = Pin<P6, Pin1, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 impl PwmPeriph<CCR3> for pac::TB3 {
     type Gpio = Pin<P6, Pin2, Alternate1<Output>>;
+    type CaptureGpio = Pin<P6, Pin2, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 impl PwmPeriph<CCR4> for pac::TB3 {
     type Gpio = Pin<P6, Pin3, Alternate1<Output>>;
+    type CaptureGpio = Pin<P6, Pin3, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 impl PwmPeriph<CCR5> for pac::TB3 {
     type Gpio = Pin<P6, Pin4, Alternate1<Output>>;
+    type CaptureGpio = Pin<P6, Pin4, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 impl PwmPeriph<CCR6> for pac::TB3 {
     type Gpio = Pin<P6, Pin5, Alternate1<Output>>;
+    type CaptureGpio = Pin<P6, Pin5, Alternate1<Input<Floating>>>;
     const ALT: Alt = Alt::Alt1;
 }
 
+// Only pins connected to a timer output get these methods, so pairing a PWM channel with the
+// wrong pin fails to compile.
+macro_rules! impl_into_pwm_output {
+    ($port:ty, $pin:ty, $to_alt:ident, $Alt:ident) => {
+        impl<PULL> Pin<$port, $pin, Input<PULL>> {
+            /// Configure the pin as the timer output expected by the `pwm` module
+            #[inline]
+            pub fn into_pwm_output(self) -> Pin<$port, $pin, $Alt<Output>> {
+                self.to_output().$to_alt()
+            }
+        }
+
+        impl Pin<$port, $pin, Output> {
+            /// Configure the pin as the timer output expected by the `pwm` module
+            #[inline]
+            pub fn into_pwm_output(self) -> Pin<$port, $pin, $Alt<Output>> {
+                self.$to_alt()
+            }
+        }
+    };
+}
+
+impl_into_pwm_output!(P1, Pin6, to_alternate2, Alternate2);
+impl_into_pwm_output!(P1, Pin7, to_alternate2, Alternate2);
+impl_into_pwm_output!(P2, Pin0, to_alternate1, Alternate1);
+impl_into_pwm_output!(P2, Pin1, to_alternate1, Alternate1);
+impl_into_pwm_output!(P5, Pin0, to_alternate1, Alternate1);
+impl_into_pwm_output!(P5, Pin1, to_alternate1, Alternate1);
+impl_into_pwm_output!(P6, Pin0, to_alternate1, Alternate1);
+impl_into_pwm_output!(P6, Pin1, to_alternate1, Alternate1);
+impl_into_pwm_output!(P6, Pin2, to_alternate1, Alternate1);
+impl_into_pwm_output!(P6, Pin3, to_alternate1, Alternate1);
+impl_into_pwm_output!(P6, Pin4, to_alternate1, Alternate1);
+impl_into_pwm_output!(P6, Pin5, to_alternate1, Alternate1);
+
 fn setup_pwm<T: TimerPeriph>(timer: &T, config: TimerConfig<T>, period: u16) {
     config.write_regs(timer);
     CCRn::<CCR0>::set_ccrn(timer, period);
@@ -116,6 +189,8 @@ pub struct PwmParts3<T: CapCmpTimer3> {
     pub pwm1: PwmUninit<T, CCR1>,
     /// PWM pin 2 (derived from capture-compare register 2)
     pub pwm2: PwmUninit<T, CCR2>,
+    /// Period shared by all the PWM pins
+    pub period: PwmPeriod<T>,
 }
 
 impl<T: CapCmpTimer3> PwmParts3<T> {
@@ -130,6 +205,7 @@ impl<T: CapCmpTimer3> PwmParts3<T> {
         Self {
             pwm1: PwmUninit::new(),
             pwm2: PwmUninit::new(),
+            period: PwmPeriod(PhantomData),
         }
     }
 }
@@ -148,6 +224,8 @@ pub struct PwmParts7<T: CapCmpTimer7> {
     pub pwm5: PwmUninit<T, CCR5>,
     /// PWM pin 6 (derived from capture-compare register 6)
     pub pwm6: PwmUninit<T, CCR6>,
+    /// Period shared by all the PWM pins
+    pub period: PwmPeriod<T>,
 }
 
 impl<T: CapCmpTimer7> PwmParts7<T> {
@@ -170,6 +248,144 @@ impl<T: CapCmpTimer7> PwmParts7<T> {
             pwm4: PwmUninit::new(),
             pwm5: PwmUninit::new(),
             pwm6: PwmUninit::new(),
+            period: PwmPeriod(PhantomData),
+        }
+    }
+}
+
+// Lets `PwmPeriod` reach the duty cycles of every PWM pin on the timer
+trait PwmTimer: TimerPeriph + CapCmp<CCR1> {
+    fn clamp_duties(&self, old_period: u16, new_period: u16);
+    // Write every compare register back with its current value, which completes a latch group
+    fn rewrite_compares(&self);
+}
+
+#[inline]
+fn clamp_duty<T: CapCmp<C>, C>(timer: &T, old_period: u16, new_period: u16) {
+    // Held pins stay on whatever the period
+    if CCRn::<C>::is_cap_mode(timer) || is_held::<T, C>(timer) {
+        return;
+    }
+    let duty = CCRn::<C>::get_ccrn(timer);
+    if duty > old_period {
+        // Keep pins that were on for the whole period on
+        set_full_duty::<T, C>(timer, new_period);
+    } else if duty > new_period {
+        CCRn::<C>::set_ccrn(timer, new_period);
+    }
+}
+
+#[inline]
+fn rewrite_compare<T: CapCmp<C>, C>(timer: &T) {
+    // Writing would overwrite the captured value
+    if !CCRn::<C>::is_cap_mode(timer) {
+        CCRn::<C>::set_ccrn(timer, CCRn::<C>::get_ccrn(timer));
+    }
+}
+
+macro_rules! impl_pwm_timer {
+    ($TBx:ident, $($CCRn:ident),*) => {
+        impl PwmTimer for pac::$TBx {
+            #[inline]
+            fn clamp_duties(&self, old_period: u16, new_period: u16) {
+                $(clamp_duty::<Self, $CCRn>(self, old_period, new_period);)*
+            }
+
+            #[inline]
+            fn rewrite_compares(&self) {
+                rewrite_compare::<Self, CCR0>(self);
+                $(rewrite_compare::<Self, $CCRn>(self);)*
+            }
+        }
+    };
+}
+
+impl_pwm_timer!(TB0, CCR1, CCR2);
+impl_pwm_timer!(TB1, CCR1, CCR2);
+impl_pwm_timer!(TB2, CCR1, CCR2);
+impl_pwm_timer!(TB3, CCR1, CCR2, CCR3, CCR4, CCR5, CCR6);
+
+/// Period shared by all the PWM pins of a timer, in timer cycles
+pub struct PwmPeriod<T>(PhantomData<T>);
+
+#[allow(private_bounds)]
+impl<T: PwmTimer> PwmPeriod<T> {
+    /// Current period
+    #[inline]
+    pub fn get_period(&self) -> u16 {
+        let timer = unsafe { T::steal() };
+        CCRn::<CCR0>::get_ccrn(&timer)
+    }
+
+    /// Change the period of every PWM pin on the timer without stopping it. Duty cycles longer than
+    /// the new period are clamped to it, except that pins which were on for the entire old period
+    /// stay on for the entire new period. Channels used as input captures are left alone.
+    ///
+    /// If the timer has already counted past the new period, it's restarted from 0 rather than
+    /// running on to 0xFFFF before wrapping, so the current period is cut short.
+    #[inline]
+    pub fn set_period(&mut self, period: u16) {
+        let timer = unsafe { T::steal() };
+        msp430::interrupt::free(|_| {
+            let old_period = CCRn::<CCR0>::get_ccrn(&timer);
+            timer.clamp_duties(old_period, period);
+            CCRn::<CCR0>::set_ccrn(&timer, period);
+            // Completes the latch group after `update_synchronized()`
+            timer.rewrite_compares();
+            if timer.tbxr_rd() > period {
+                timer.reset();
+            }
+        });
+    }
+
+    /// Run `update`, then make every duty cycle and period it writes on this timer take effect
+    /// together at the start of the next period, which matters when driving things like motor
+    /// phases or RGB LEDs.
+    ///
+    /// This groups all of the timer's compare latches (TBCLGRP), so they're only loaded at the
+    /// start of a period once every one of them has been written, and then writes back the
+    /// registers that `update` left alone. The group loads on the latch mode of the pin on CCR1,
+    /// which is switched to latched mode. Channels used as input captures aren't written.
+    ///
+    /// The latches stay grouped afterwards, so a duty cycle set outside of
+    /// `update_synchronized()` is held back until the next call or `set_period()`. Call
+    /// `ungroup()` to update pins independently again.
+    #[inline]
+    pub fn update_synchronized<R>(&mut self, update: impl FnOnce() -> R) -> R {
+        let timer = unsafe { T::steal() };
+        CCRn::<CCR1>::config_clld(&timer, Clld::OnZero);
+        timer.set_tbclgrp(Tbclgrp::All);
+        let res = update();
+        timer.rewrite_compares();
+        res
+    }
+
+    /// Stop grouping the compare latches after `update_synchronized()`, so each pin picks up new
+    /// duty cycles according to its own latch mode again. Duty cycles still waiting on the group
+    /// are written again first, so they aren't lost.
+    #[inline]
+    pub fn ungroup(&mut self) {
+        let timer = unsafe { T::steal() };
+        timer.rewrite_compares();
+        timer.set_tbclgrp(Tbclgrp::Individual);
+    }
+}
+
+/// Output polarity of a PWM pin
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Polarity {
+    /// Output is high for `duty` cycles, then low for the rest of the period
+    Normal,
+    /// Output is low for `duty` cycles, then high for the rest of the period
+    Inverted,
+}
+
+impl From<Polarity> for Outmod {
+    #[inline(always)]
+    fn from(polarity: Polarity) -> Self {
+        match polarity {
+            Polarity::Normal => Outmod::ResetSet,
+            Polarity::Inverted => Outmod::SetReset,
         }
     }
 }
@@ -184,10 +400,32 @@ impl<T: PwmPeriph<C>, C> PwmUninit<T, C> {
             _timer: PhantomData,
             _ccrn: PhantomData,
             pin,
+            latched: false,
+            polarity: Polarity::Normal,
         }
     }
 }
 
+impl<T: PwmPeriph<C>, C> PwmUninit<T, C> {
+    /// Use the channel as an input capture triggered by capture input A, which requires a
+    /// correctly configured GPIO pin. Captures are synchronized to the timer clock.
+    #[inline]
+    pub fn into_capture_input_a(self, _gpio: T::CaptureGpio, trigger: CapTrigger) -> Capture<T, C> {
+        let timer = unsafe { T::steal() };
+        CCRn::<C>::config_cap_mode(&timer, trigger.into(), Ccis::InputA, true);
+        Capture::new()
+    }
+
+    /// Use the channel as an input capture triggered by capture input B. Captures are
+    /// synchronized to the timer clock.
+    #[inline]
+    pub fn into_capture_input_b(self, trigger: CapTrigger) -> Capture<T, C> {
+        let timer = unsafe { T::steal() };
+        CCRn::<C>::config_cap_mode(&timer, trigger.into(), Ccis::InputB, true);
+        Capture::new()
+    }
+}
+
 impl<T, C> PwmUninit<T, C> {
     fn new() -> Self {
         Self(PhantomData, PhantomData)
@@ -199,6 +437,127 @@ pub struct Pwm<T: PwmPeriph<C>, C> {
     _timer: PhantomData<T>,
     _ccrn: PhantomData<C>,
     pin: T::Gpio,
+    latched: bool,
+    polarity: Polarity,
+}
+
+impl<T: PwmPeriph<C>, C> Pwm<T, C> {
+    /// Set the output polarity of the pin. The duty cycle keeps its meaning, so a duty of 0 with
+    /// inverted polarity keeps the output high for the entire period.
+    #[inline]
+    pub fn set_polarity(&mut self, polarity: Polarity) {
+        let timer = unsafe { T::steal() };
+        self.polarity = polarity;
+        if is_held::<T, C>(&timer) {
+            CCRn::<C>::config_out(&timer, polarity == Polarity::Normal);
+        } else {
+            self.restore_outmod(&timer);
+        }
+    }
+
+    // Put the output back under the timer's control with the pin's polarity and latch mode
+    #[inline(always)]
+    fn restore_outmod(&self, timer: &T) {
+        CCRn::<C>::config_outmod(timer, self.polarity.into());
+        // Writing the output mode also resets the latch mode
+        if self.latched {
+            CCRn::<C>::config_clld(timer, Clld::OnZero);
+        }
+    }
+
+    /// Enable or disable latched duty updates. While latched, a new duty cycle is held back until
+    /// the timer wraps around to the start of the next period, so the current period always
+    /// finishes with the old duty. This avoids glitches such as a missed reset when the duty is
+    /// lowered below the current count.
+    #[inline]
+    pub fn set_latched(&mut self, latched: bool) {
+        let timer = unsafe { T::steal() };
+        let clld = if latched {
+            Clld::OnZero
+        } else {
+            Clld::Immediate
+        };
+        CCRn::<C>::config_clld(&timer, clld);
+        self.latched = latched;
+    }
+
+    /// Set the duty cycle to `numerator / denominator` of the current period, rounded to the
+    /// nearest cycle. A fraction of 1 or more (including a `denominator` of 0) keeps the output on
+    /// for the entire period, rather than glitching off for a cycle when the duty is exactly equal
+    /// to the period. This sets the duty past the period, or holds the output on when the period
+    /// is 0xFFFF, until the next duty cycle is set.
+    #[inline]
+    pub fn set_duty_fraction(&mut self, numerator: u16, denominator: u16) {
+        let period = self.get_max_duty();
+        match duty_from_fraction(numerator, denominator, period) {
+            Some(duty) => self.set_duty(duty),
+            None => {
+                let timer = unsafe { T::steal() };
+                set_full_duty::<T, C>(&timer, period);
+            }
+        }
+    }
+
+    /// Set the duty cycle as a percentage of the current period, rounded to the nearest cycle.
+    /// Percentages above 100 are treated as 100, which keeps the output on for the entire period.
+    #[inline]
+    pub fn set_duty_percent(&mut self, percent: u8) {
+        self.set_duty_fraction(percent.min(100) as u16, 100);
+    }
+}
+
+// Duty cycle for `numerator / denominator` of the period, or `None` if the output should stay on
+// for the entire period
+#[inline]
+const fn duty_from_fraction(numerator: u16, denominator: u16, period: u16) -> Option<u16> {
+    if numerator >= denominator {
+        None
+    } else {
+        let (num, den) = (numerator as u32, denominator as u32);
+        // Fits in u16 since num < den, so the result is at most the period
+        Some(((num * period as u32 + den / 2) / den) as u16)
+    }
+}
+
+// The corner cases of `duty_from_fraction()`, checked at compile time
+const _: () = {
+    assert!(matches!(duty_from_fraction(0, 100, 1000), Some(0)));
+    assert!(matches!(duty_from_fraction(0, 100, 0xFFFF), Some(0)));
+    assert!(matches!(duty_from_fraction(100, 100, 1000), None));
+    assert!(matches!(duty_from_fraction(100, 100, 0xFFFF), None));
+    assert!(matches!(duty_from_fraction(0, 0, 1000), None));
+    assert!(matches!(duty_from_fraction(1, 2, 0xFFFF), Some(0x8000)));
+    assert!(matches!(duty_from_fraction(99, 100, 1000), Some(990)));
+    assert!(matches!(
+        duty_from_fraction(0xFFFE, 0xFFFF, 0xFFFF),
+        Some(0xFFFE)
+    ));
+    assert!(matches!(duty_from_fraction(1, 3, 1), Some(0)));
+    assert!(matches!(duty_from_fraction(2, 3, 1), Some(1)));
+};
+
+// Whether the output is held at a fixed level by `set_full_duty()`
+#[inline(always)]
+fn is_held<T: CapCmp<C>, C>(timer: &T) -> bool {
+    CCRn::<C>::outmod_rd(timer) == Outmod::Out as u8
+}
+
+// Keep a PWM output on for the entire period. A duty past the period does this, except when the
+// period is 0xFFFF and no such duty exists, in which case the output is held at its on level in
+// output mode 0 instead
+#[inline]
+fn set_full_duty<T: CapCmp<C>, C>(timer: &T, period: u16) {
+    if is_held::<T, C>(timer) {
+        return;
+    }
+    match period.checked_add(1) {
+        Some(duty) => CCRn::<C>::set_ccrn(timer, duty),
+        None => {
+            let on_high = CCRn::<C>::outmod_rd(timer) == Outmod::ResetSet as u8;
+            CCRn::<C>::set_ccrn(timer, period);
+            CCRn::<C>::config_out(timer, on_high);
+        }
+    }
 }
 
 impl<T: PwmPeriph<C>, C> PwmPin for Pwm<T, C> {
@@ -209,6 +568,9 @@ impl<T: PwmPeriph<C>, C> PwmPin for Pwm<T, C> {
     fn set_duty(&mut self, duty: Self::Duty) {
         let timer = unsafe { T::steal() };
         CCRn::<C>::set_ccrn(&timer, duty);
+        if is_held::<T, C>(&timer) {
+            self.restore_outmod(&timer);
+        }
     }
 
     #[inline]
@@ -235,3 +597,243 @@ impl<T: PwmPeriph<C>, C> PwmPin for Pwm<T, C> {
         T::to_alt(&mut self.pin);
     }
 }
+
+// Shortest pulse kept by `PwmComplementary`, in timer cycles. Toggling outputs are only switched on
+// at the bottom of the count, and the switch has to finish before the timer reaches the low side's
+// compare value, so shorter low-side pulses are dropped. Assumes the timer isn't clocked faster
+// than MCLK.
+const MIN_PULSE: u16 = 64;
+// How many times `PwmComplementary` polls for the bottom of the count before giving up on a switch
+const SWITCH_POLLS: u32 = 200_000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Low,
+    High,
+    // Toggling at the contained compare value
+    Toggling(u16),
+}
+
+impl Level {
+    #[inline]
+    fn same_kind(self, other: Level) -> bool {
+        matches!(
+            (self, other),
+            (Level::Low, Level::Low)
+                | (Level::High, Level::High)
+                | (Level::Toggling(_), Level::Toggling(_))
+        )
+    }
+}
+
+// In up/down mode the high side is on while the count is above its compare value, and the low side
+// is on while the count is below its own, so the gap between the two compare values is the dead
+// time on both edges.
+#[inline]
+fn complementary_levels(period: u16, duty: u16, dead_time: u16) -> (Level, Level) {
+    let duty = duty.min(period);
+    let high_cmp = period - duty;
+    let low_cmp = high_cmp.saturating_sub(dead_time);
+
+    let high = if duty == 0 {
+        Level::Low
+    } else if high_cmp < MIN_PULSE {
+        Level::High
+    } else {
+        Level::Toggling(high_cmp)
+    };
+    let low = if high == Level::High || low_cmp < MIN_PULSE {
+        Level::Low
+    } else if low_cmp >= period {
+        Level::High
+    } else {
+        Level::Toggling(low_cmp)
+    };
+    (high, low)
+}
+
+/// Pair of complementary PWM outputs with a dead time between them, for driving the two switches
+/// of a half-bridge. Uses capture-compare registers 1 and 2 of the timer, with the timer running in
+/// up/down mode.
+///
+/// The output period is `2 * period` timer cycles. The duty is given out of `period`, and the
+/// high side is on for `2 * duty` cycles of each output period. The low side is on for the rest,
+/// minus `dead_time` cycles on either side where both outputs are off, so the two outputs are never
+/// on at the same time.
+///
+/// A duty of 0 keeps the high side off, and a duty close enough to `period` that the low side's
+/// pulse would be shorter than 64 cycles keeps the high side on and the low side off. Changes
+/// between these fixed states and normal switching park both outputs off, then wait for the
+/// bottom of the count to switch over. Interrupts stay enabled while waiting, since both outputs
+/// are off, but the timer's TBIFG flag must not be cleared by an ISR in the meantime. If the bottom
+/// isn't reached within a bounded number of polls, for example because the timer is stopped, the
+/// outputs are left off and the switch is retried on the next change. Other duty changes take
+/// effect at the start of the next period without blocking.
+pub struct PwmComplementary<T: TimerPeriph + PwmPeriph<CCR1> + PwmPeriph<CCR2>> {
+    high_pin: <T as PwmPeriph<CCR1>>::Gpio,
+    low_pin: <T as PwmPeriph<CCR2>>::Gpio,
+    duty: u16,
+    dead_time: u16,
+    levels: (Level, Level),
+}
+
+impl<T: TimerPeriph + PwmPeriph<CCR1> + PwmPeriph<CCR2>> PwmComplementary<T> {
+    /// Configure the timer for complementary PWM, with the high side on capture-compare register 1
+    /// and the low side on capture-compare register 2. The duty starts at 0.
+    pub fn new(
+        timer: T,
+        config: TimerConfig<T>,
+        period: u16,
+        dead_time: u16,
+        high_pin: <T as PwmPeriph<CCR1>>::Gpio,
+        low_pin: <T as PwmPeriph<CCR2>>::Gpio,
+    ) -> Self {
+        setup_pwm(&timer, config, period);
+        CCRn::<CCR1>::config_out(&timer, false);
+        CCRn::<CCR2>::config_out(&timer, false);
+        timer.updown();
+        let mut pwm = Self {
+            high_pin,
+            low_pin,
+            duty: 0,
+            dead_time,
+            levels: (Level::Low, Level::Low),
+        };
+        pwm.update();
+        pwm
+    }
+
+    /// Set the duty of the high side, out of the period. Values above the period are treated as
+    /// the period.
+    #[inline]
+    pub fn set_duty(&mut self, duty: u16) {
+        self.duty = duty;
+        self.update();
+    }
+
+    /// Duty of the high side
+    #[inline]
+    pub fn get_duty(&self) -> u16 {
+        self.duty
+    }
+
+    /// Set the number of timer cycles both outputs stay off between one turning off and the other
+    /// turning on
+    #[inline]
+    pub fn set_dead_time(&mut self, dead_time: u16) {
+        self.dead_time = dead_time;
+        self.update();
+    }
+
+    /// Dead time between the outputs, in timer cycles
+    #[inline]
+    pub fn get_dead_time(&self) -> u16 {
+        self.dead_time
+    }
+
+    /// Half the output period, in timer cycles
+    #[inline]
+    pub fn get_period(&self) -> u16 {
+        let timer = unsafe { T::steal() };
+        CCRn::<CCR0>::get_ccrn(&timer)
+    }
+
+    /// Disconnect both outputs from the timer, returning the pins to their GPIO function
+    #[inline]
+    pub fn disable(&mut self) {
+        <T as PwmPeriph<CCR1>>::to_gpio(&mut self.high_pin);
+        <T as PwmPeriph<CCR2>>::to_gpio(&mut self.low_pin);
+    }
+
+    /// Reconnect both outputs to the timer
+    #[inline]
+    pub fn enable(&mut self) {
+        <T as PwmPeriph<CCR1>>::to_alt(&mut self.high_pin);
+        <T as PwmPeriph<CCR2>>::to_alt(&mut self.low_pin);
+    }
+
+    /// Park both outputs off, stop the timer, and give back the timer and both pins
+    #[inline]
+    pub fn free(
+        self,
+    ) -> (
+        T,
+        <T as PwmPeriph<CCR1>>::Gpio,
+        <T as PwmPeriph<CCR2>>::Gpio,
+    ) {
+        let timer = unsafe { T::steal() };
+        CCRn::<CCR1>::config_out(&timer, false);
+        CCRn::<CCR2>::config_out(&timer, false);
+        timer.stop();
+        (timer, self.high_pin, self.low_pin)
+    }
+
+    fn update(&mut self) {
+        let timer = unsafe { T::steal() };
+        let (high, low) = complementary_levels(self.get_period(), self.duty, self.dead_time);
+        let (old_high, old_low) = self.levels;
+
+        if high.same_kind(old_high) && low.same_kind(old_low) {
+            // The compare values are latched at the bottom of the count, where both outputs are in
+            // the same state for any compare value, so they can be changed at any time
+            msp430::interrupt::free(|_| {
+                if let Level::Toggling(cmp) = high {
+                    CCRn::<CCR1>::set_ccrn(&timer, cmp);
+                }
+                if let Level::Toggling(cmp) = low {
+                    CCRn::<CCR2>::set_ccrn(&timer, cmp);
+                }
+            });
+            self.levels = (high, low);
+            return;
+        }
+
+        // Park both outputs off, then switch over at the bottom of the count, where a toggling
+        // high side is off and a toggling low side is on
+        msp430::interrupt::free(|_| {
+            CCRn::<CCR1>::config_out(&timer, false);
+            CCRn::<CCR2>::config_out(&timer, false);
+            if let Level::Toggling(cmp) = high {
+                CCRn::<CCR1>::set_ccrn(&timer, cmp);
+            }
+            if let Level::Toggling(cmp) = low {
+                CCRn::<CCR2>::set_ccrn(&timer, cmp);
+            }
+            timer.tbifg_clr();
+        });
+
+        // Both outputs are off while parked, so only the check and switch need interrupts disabled
+        let switched = !timer.is_stopped()
+            && (0..SWITCH_POLLS).any(|_| {
+                msp430::interrupt::free(|_| {
+                    if !timer.tbifg_rd() {
+                        return false;
+                    }
+                    match high {
+                        Level::Low => (),
+                        Level::High => CCRn::<CCR1>::config_out(&timer, true),
+                        Level::Toggling(_) => {
+                            CCRn::<CCR1>::config_outmod(&timer, Outmod::ToggleSet);
+                            CCRn::<CCR1>::config_clld(&timer, Clld::OnZero);
+                        }
+                    }
+                    match low {
+                        Level::Low => (),
+                        Level::High => CCRn::<CCR2>::config_out(&timer, true),
+                        Level::Toggling(_) => {
+                            CCRn::<CCR2>::config_out(&timer, true);
+                            CCRn::<CCR2>::config_outmod(&timer, Outmod::ToggleReset);
+                            CCRn::<CCR2>::config_clld(&timer, Clld::OnZero);
+                        }
+                    }
+                    true
+                })
+            });
+        // Outputs that were left parked are off, so the next update retries the switch
+        self.levels = if switched {
+            (high, low)
+        } else {
+            (Level::Low, Level::Low)
+        };
+    }
+}
@@ -4,13 +4,33 @@
 //! After configuring the E_USCI peripherals, serial Rx and/or Tx pins can be obtained by
 //! converting the appropriate GPIO pins to the alternate function corresponding to UART.
 //!
-//! The Tx and Rx pins are used to send and receive bytes via serial connection.
+//! The Tx and Rx pins are used to send and receive bytes via serial connection. When a single
+//! owner handles both directions, they can be combined into a `Serial`.
+//!
+//! `Tx` and `Rx` are `Send`, and can be used from different tasks or interrupt priorities (e.g.
+//! as separate RTIC resources) without locking. `Tx` only touches UCTXIFG, UCTXIE and the Tx
+//! buffer, while `Rx` only touches UCRXIFG, UCRXIE and the Rx buffer. Interrupt enables are
+//! changed with single-instruction bit set/clear operations, and `set_framing()`, which resets
+//! the whole UART, runs in a critical section.
+//!
+//! Multidrop buses can use one of the multiprocessor modes selected with
+//! `SerialConfig::multiprocessor_mode()`. In address-bit mode, `Tx::send_address()` marks a byte
+//! as an address, and a receiver in dormant mode (`Rx::set_dormant()`) ignores every byte that
+//! isn't an address, so it only wakes up when it's addressed.
+//!
+//! `BufferedTx` and `BufferedRx` move bytes between the UART and a ring buffer from the UART's
+//! ISR, so bulk writes don't have to wait on the single-byte Tx buffer and received bytes aren't
+//! lost while the main loop is busy.
+//!
+//! For half-duplex RS-485 links, `Rs485` wraps a `Tx` pin together with the transceiver's
+//! driver-enable output and takes care of toggling it around each transmission.
 
 use crate::clock::{Aclk, Clock, Smclk};
 use crate::gpio::{Alternate1, Pin, Pin1, Pin2, Pin3, Pin5, Pin6, Pin7, P1, P4};
 use crate::hw_traits::eusci::{EUsciUart, UartUcxStatw, UcaCtlw0, Ucssel};
 use core::marker::PhantomData;
 use core::num::NonZeroU32;
+use embedded_hal::digital::v2::OutputPin;
 use embedded_hal::serial::{Read, Write};
 use msp430fr2355 as pac;
 
@@ -120,6 +140,41 @@ impl Loopback {
     }
 }
 
+/// Multiprocessor format used to distinguish address bytes from data bytes on a multidrop bus
+#[derive(Clone, Copy)]
+pub enum MultiprocessorMode {
+    /// Plain UART with no multiprocessor format (the default)
+    Off,
+    /// Idle-line mode, where the first byte after an idle period of 10 or more bit times is an
+    /// address
+    IdleLine,
+    /// Address-bit mode, where each byte carries an extra bit marking it as an address
+    AddressBit,
+}
+
+impl MultiprocessorMode {
+    #[inline(always)]
+    fn ucmode(self) -> u8 {
+        match self {
+            MultiprocessorMode::Off => 0b00,
+            MultiprocessorMode::IdleLine => 0b01,
+            MultiprocessorMode::AddressBit => 0b10,
+        }
+    }
+}
+
+/// Baud rate generation mode, which determines how many times each bit is sampled
+#[derive(Clone, Copy)]
+pub enum Oversampling {
+    /// Use oversampling if the clock is at least 16 times the baud rate (the default)
+    Auto,
+    /// Always use oversampling mode (UCOS16 set). Improves tolerance at low baud rates.
+    Oversampled,
+    /// Always use low-frequency mode (UCOS16 cleared). Needed at high baud rates relative to
+    /// the clock.
+    LowFrequency,
+}
+
 /// Marks a USCI type that can be used as a serial UART
 pub trait SerialUsci: EUsciUart {
     /// Pin used for serial UCLK
@@ -180,6 +235,7 @@ impl_serial_pin!(UsciA1RxPin, P4, Pin2);
 /// Typestate for a serial interface with an unspecified clock source
 pub struct NoClockSet {
     baudrate: NonZeroU32,
+    oversampling: Oversampling,
 }
 
 /// Typestate for a serial interface with a specified clock source
@@ -199,6 +255,7 @@ pub struct SerialConfig<USCI: SerialUsci, S> {
     stopbits: StopBits,
     parity: Parity,
     loopback: Loopback,
+    mp_mode: MultiprocessorMode,
     state: S,
 }
 
@@ -211,6 +268,7 @@ macro_rules! serial_config {
             stopbits: $conf.stopbits,
             parity: $conf.parity,
             loopback: $conf.loopback,
+            mp_mode: $conf.mp_mode,
             state: $state,
         }
     };
@@ -236,12 +294,29 @@ impl<USCI: SerialUsci> SerialConfig<USCI, NoClockSet> {
             parity,
             loopback,
             usci,
+            mp_mode: MultiprocessorMode::Off,
             state: NoClockSet {
                 baudrate: NonZeroU32::new(baudrate).unwrap_or(ONE),
+                oversampling: Oversampling::Auto,
             },
         }
     }
 
+    /// Override the automatic choice of baud rate generation mode. The baud rate divisors are
+    /// still calculated from the clock frequency once the clock source is selected.
+    #[inline(always)]
+    pub fn oversampling(mut self, oversampling: Oversampling) -> Self {
+        self.state.oversampling = oversampling;
+        self
+    }
+
+    /// Select a multiprocessor format for communicating on a multidrop bus
+    #[inline(always)]
+    pub fn multiprocessor_mode(mut self, mode: MultiprocessorMode) -> Self {
+        self.mp_mode = mode;
+        self
+    }
+
     /// Configure serial UART to use external UCLK, passing in the appropriately configured pin
     /// used as the clock signal as well as the frequency of the clock.
     #[inline(always)]
@@ -253,7 +328,11 @@ impl<USCI: SerialUsci> SerialConfig<USCI, NoClockSet> {
         serial_config!(
             self,
             ClockSet {
-                baud_config: calculate_baud_config(freq, self.state.baudrate),
+                baud_config: calculate_baud_config(
+                    freq,
+                    self.state.baudrate,
+                    self.state.oversampling,
+                ),
                 clksel: Ucssel::Uclk,
             }
         )
@@ -265,7 +344,11 @@ impl<USCI: SerialUsci> SerialConfig<USCI, NoClockSet> {
         serial_config!(
             self,
             ClockSet {
-                baud_config: calculate_baud_config(aclk.freq() as u32, self.state.baudrate),
+                baud_config: calculate_baud_config(
+                    aclk.freq() as u32,
+                    self.state.baudrate,
+                    self.state.oversampling,
+                ),
                 clksel: Ucssel::Aclk,
             }
         )
@@ -277,7 +360,11 @@ impl<USCI: SerialUsci> SerialConfig<USCI, NoClockSet> {
         serial_config!(
             self,
             ClockSet {
-                baud_config: calculate_baud_config(smclk.freq(), self.state.baudrate),
+                baud_config: calculate_baud_config(
+                    smclk.freq(),
+                    self.state.baudrate,
+                    self.state.oversampling,
+                ),
                 clksel: Ucssel::Smclk,
             }
         )
@@ -292,19 +379,29 @@ struct BaudConfig {
 }
 
 #[inline]
-fn calculate_baud_config(clk_freq: u32, bps: NonZeroU32) -> BaudConfig {
+fn calculate_baud_config(
+    clk_freq: u32,
+    bps: NonZeroU32,
+    oversampling: Oversampling,
+) -> BaudConfig {
     // Ensure n stays within the 16 bit boundary
     let n = (clk_freq / bps).clamp(1, 0xFFFF);
 
     let brs = lookup_brs(clk_freq, bps);
 
-    if (n >= 16) && (bps.get() < u32::MAX / 16) {
+    let ucos16 = match oversampling {
+        Oversampling::Auto => n >= 16,
+        Oversampling::Oversampled => true,
+        Oversampling::LowFrequency => false,
+    };
+
+    if ucos16 && (bps.get() < u32::MAX / 16) {
         //  div = bps * 16
         const SIXTEEN: NonZeroU32 = NonZeroU32::new(16).unwrap();
         let div = bps.saturating_mul(SIXTEEN);
 
-        // n / 16, but more precise
-        let br = (clk_freq / div) as u16;
+        // n / 16, but more precise. Can only be 0 if oversampling was forced on a slow clock
+        let br = ((clk_freq / div) as u16).max(1);
 
         // same as n % 16, but more precise
         let brf = ((clk_freq % div) / bps) as u8;
@@ -408,6 +505,9 @@ impl<USCI: SerialUsci> SerialConfig<USCI, ClockSet> {
             ucssel: clksel,
             // We want erroneous bytes to trigger RXIFG so all errors can be caught
             ucrxeie: true,
+            // Breaks also trigger RXIFG so they can be reported as errors
+            ucbrkie: true,
+            ucmode: self.mp_mode.ucmode(),
         });
     }
 
@@ -437,10 +537,66 @@ impl<USCI: SerialUsci> SerialConfig<USCI, ClockSet> {
     }
 }
 
+#[inline]
+fn set_framing<USCI: SerialUsci>(parity: Parity, stopbits: StopBits) {
+    let usci = unsafe { USCI::steal() };
+    // Changing the framing saves and restores the whole IE register around the reset, so it must
+    // not be preempted by the other half toggling its own interrupt enable
+    msp430::interrupt::free(|_| {
+        usci.framing_settings(parity.ucpen(), parity.ucpar(), stopbits.to_bool())
+    });
+}
+
+/// Indicates which event caused the UART interrupt to fire
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UartVector {
+    /// No pending interrupt
+    NoInterrupt,
+    /// A byte was received, possibly with an error, and is ready to be read
+    RxReady,
+    /// The Tx buffer is empty and ready for the next byte
+    TxEmpty,
+    /// A start bit was received
+    StartBit,
+    /// The last byte has been completely shifted out, including its stop bits
+    TxComplete,
+}
+
+#[inline]
+fn read_uart_vector<USCI: SerialUsci>() -> UartVector {
+    let usci = unsafe { USCI::steal() };
+    match usci.iv_rd() {
+        0 => UartVector::NoInterrupt,
+        2 => UartVector::RxReady,
+        4 => UartVector::TxEmpty,
+        6 => UartVector::StartBit,
+        8 => UartVector::TxComplete,
+        _ => unsafe { core::hint::unreachable_unchecked() },
+    }
+}
+
 /// Serial transmitter pin
 pub struct Tx<USCI: SerialUsci>(PhantomData<USCI>);
 
 impl<USCI: SerialUsci> Tx<USCI> {
+    /// Change the parity and number of stop bits at runtime, keeping the baud rate, clock source
+    /// and interrupt enables. The UART is briefly reset, so any byte being sent or received is
+    /// lost. Since the framing settings are shared by both directions, this also affects the Rx
+    /// pin of the same UART.
+    #[inline]
+    pub fn set_framing(&mut self, parity: Parity, stopbits: StopBits) {
+        set_framing::<USCI>(parity, stopbits);
+    }
+
+    /// Read the UART interrupt vector, which reports the highest priority pending interrupt.
+    /// Reading the vector automatically clears the corresponding interrupt flag, though for
+    /// `RxReady` the received byte must still be read. Tx and Rx share the vector, so only read
+    /// it from the single ISR that services both directions.
+    #[inline]
+    pub fn interrupt_source(&mut self) -> UartVector {
+        read_uart_vector::<USCI>()
+    }
+
     /// Enable Tx interrupts, which fire when ready to send.
     #[inline(always)]
     pub fn enable_tx_interrupts(&mut self) {
@@ -454,6 +610,51 @@ impl<USCI: SerialUsci> Tx<USCI> {
         let usci = unsafe { USCI::steal() };
         usci.txie_clear();
     }
+
+    /// Send `addr` as an address byte in a multiprocessor mode. In address-bit mode the byte is
+    /// sent with its address bit set, while in idle-line mode an idle period is sent before it.
+    /// The address flag has to be set before the byte is loaded into the Tx buffer, so this blocks
+    /// on the Tx flag in the same way as `write()`.
+    #[inline]
+    pub fn send_address(&mut self, addr: u8) -> nb::Result<(), void::Void> {
+        let usci = unsafe { USCI::steal() };
+        if usci.txifg_rd() {
+            usci.txaddr_set();
+            usci.tx_wr(addr);
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Send a break, holding the Tx line low for a whole frame, as used by protocols such as LIN to
+    /// mark the start of a message. Blocks until the break has completely left the Tx pin.
+    #[inline]
+    pub fn send_break(&mut self) {
+        let usci = unsafe { USCI::steal() };
+        while !usci.txifg_rd() {}
+        usci.txbrk_set();
+        // The break is sent in place of this dummy byte
+        usci.tx_wr(0);
+        nb::block!(self.wait_tx_complete()).unwrap();
+    }
+
+    /// Check whether the last byte has been fully shifted out of the Tx pin, including its stop
+    /// bits. Unlike `flush()`, which only waits for the Tx buffer to empty, this is suitable for
+    /// deciding when to release the driver-enable line of a half-duplex (e.g. RS-485) transceiver.
+    ///
+    /// Because of errata USCI42, UCTXCPTIFG can't be relied upon, so this instead waits for the Tx
+    /// buffer to empty and for UCBUSY to clear. UCBUSY also reflects receive activity, so if the
+    /// receiver is active this may report completion slightly later than necessary.
+    #[inline]
+    pub fn wait_tx_complete(&mut self) -> nb::Result<(), void::Void> {
+        let usci = unsafe { USCI::steal() };
+        if usci.txifg_rd() && !usci.statw_rd().ucbusy() {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
 }
 
 impl<USCI: SerialUsci> Write<u8> for Tx<USCI> {
@@ -461,7 +662,8 @@ impl<USCI: SerialUsci> Write<u8> for Tx<USCI> {
 
     /// Due to errata USCI42, UCTXCPTIFG will fire every time a byte is done transmitting,
     /// even if there's still more buffered. Thus, the implementation uses UCTXIFG instead. When
-    /// `flush()` completes, the Tx buffer will be empty but the FIFO may still be sending.
+    /// `flush()` completes, the Tx buffer will be empty but the FIFO may still be sending. Use
+    /// `Tx::wait_tx_complete()` to wait for the transmission to physically finish.
     #[inline]
     fn flush(&mut self) -> nb::Result<(), Self::Error> {
         let usci = unsafe { USCI::steal() };
@@ -488,10 +690,75 @@ impl<USCI: SerialUsci> Write<u8> for Tx<USCI> {
 
 impl<USCI: SerialUsci> embedded_hal::blocking::serial::write::Default<u8> for Tx<USCI> {}
 
+/// RS-485 transmitter that drives a transceiver's driver-enable (DE) pin
+///
+/// DE is asserted before the first byte is sent and released only once the last stop bit has
+/// left the Tx pin. Transceivers with an active-low receiver-enable can tie it to DE so the
+/// receiver is switched back on as soon as the driver is released.
+pub struct Rs485<USCI: SerialUsci, DE: OutputPin> {
+    tx: Tx<USCI>,
+    de: DE,
+}
+
+impl<USCI: SerialUsci, DE: OutputPin> Rs485<USCI, DE> {
+    /// Create an RS-485 transmitter, releasing the DE pin
+    #[inline]
+    pub fn new(tx: Tx<USCI>, mut de: DE) -> Result<Self, DE::Error> {
+        de.set_low()?;
+        Ok(Rs485 { tx, de })
+    }
+
+    /// Release the Tx pin and the DE pin
+    #[inline]
+    pub fn free(self) -> (Tx<USCI>, DE) {
+        (self.tx, self.de)
+    }
+}
+
+impl<USCI: SerialUsci, DE: OutputPin> embedded_hal::blocking::serial::Write<u8>
+    for Rs485<USCI, DE>
+{
+    type Error = DE::Error;
+
+    /// Assert DE, send all bytes, wait for the transmission to physically finish, then release DE
+    fn bwrite_all(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.de.set_high()?;
+        for &byte in buffer {
+            nb::block!(self.tx.write(byte)).unwrap();
+        }
+        nb::block!(self.tx.wait_tx_complete()).unwrap();
+        self.de.set_low()
+    }
+
+    #[inline]
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        nb::block!(self.tx.wait_tx_complete()).unwrap();
+        Ok(())
+    }
+}
+
 /// Serial receiver pin
 pub struct Rx<USCI: SerialUsci>(PhantomData<USCI>);
 
 impl<USCI: SerialUsci> Rx<USCI> {
+    /// Change the parity and number of stop bits at runtime, keeping the baud rate, clock source
+    /// and interrupt enables. The UART is briefly reset, so any byte being sent or received is
+    /// lost. Since the framing settings are shared by both directions, this also affects the Tx
+    /// pin of the same UART.
+    #[inline]
+    pub fn set_framing(&mut self, parity: Parity, stopbits: StopBits) {
+        set_framing::<USCI>(parity, stopbits);
+    }
+
+    /// Read the UART interrupt vector, which reports the highest priority pending interrupt.
+    /// Reading the vector automatically clears the corresponding interrupt flag, though for
+    /// `RxReady` the received byte must still be read. Tx and Rx share the vector, so only read
+    /// it from the single ISR that services both directions.
+    #[inline]
+    pub fn interrupt_source(&mut self) -> UartVector {
+        read_uart_vector::<USCI>()
+    }
+
     /// Enable Rx interrupts, which fire when ready to read
     #[inline(always)]
     pub fn enable_rx_interrupts(&mut self) {
@@ -506,6 +773,32 @@ impl<USCI: SerialUsci> Rx<USCI> {
         usci.rxie_clear();
     }
 
+    /// Enter or leave dormant mode. While dormant in a multiprocessor mode, only address bytes set
+    /// the Rx flag, so the receiver ignores data meant for other devices. After receiving its own
+    /// address, software should leave dormant mode to receive the following data bytes, then
+    /// re-enter it at the end of the message.
+    #[inline]
+    pub fn set_dormant(&mut self, dormant: bool) {
+        let usci = unsafe { USCI::steal() };
+        if dormant {
+            usci.dorm_set();
+        } else {
+            usci.dorm_clear();
+        }
+    }
+
+    /// Like `read()`, but also reports whether the byte was an address in a multiprocessor mode
+    #[inline]
+    pub fn read_with_address(&mut self) -> nb::Result<(u8, bool), RecvError> {
+        let usci = unsafe { USCI::steal() };
+        if !usci.rxifg_rd() {
+            return Err(nb::Error::WouldBlock);
+        }
+        // The address flag is cleared by reading the Rx buffer, so check it first
+        let is_addr = usci.statw_rd().ucaddr();
+        self.read().map(|byte| (byte, is_addr))
+    }
+
     /// Reads raw value from Rx buffer with no checks for validity
     /// # Safety
     /// May read duplicate data
@@ -515,6 +808,26 @@ impl<USCI: SerialUsci> Rx<USCI> {
         usci.rx_rd()
     }
 
+    /// Read every byte that's immediately available into `buf` without blocking, returning the
+    /// number of bytes read. Since the receive buffer only holds a single byte this will usually
+    /// be 0 or 1, but it allows polling loops to drain the receiver with a uniform interface.
+    ///
+    /// If a receive error is encountered it's returned immediately, although bytes read before
+    /// the error will already have been written into `buf`.
+    #[inline]
+    pub fn read_available(&mut self, buf: &mut [u8]) -> Result<usize, RecvError> {
+        let mut count = 0;
+        for slot in buf.iter_mut() {
+            match self.read() {
+                Ok(byte) => *slot = byte,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(err)) => return Err(err),
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
     #[inline(always)]
     /// Writes a byte into the Tx buffer with no checks for validity
     /// # Safety
@@ -526,6 +839,10 @@ impl<USCI: SerialUsci> Rx<USCI> {
 }
 
 /// Serial receive errors
+///
+/// A break also violates the stop bit, so it's usually accompanied by a framing error. Breaks are
+/// checked first, so a break is reported as `Break` rather than `Framing`.
+#[derive(Clone, Copy, Debug)]
 pub enum RecvError {
     /// Framing error
     Framing,
@@ -533,6 +850,8 @@ pub enum RecvError {
     Parity,
     /// Buffer overrun error. Contains the most recently read byte, which is still valid.
     Overrun(u8),
+    /// Break received
+    Break,
 }
 
 impl<USCI: SerialUsci> Read<u8> for Rx<USCI> {
@@ -549,7 +868,9 @@ impl<USCI: SerialUsci> Read<u8> for Rx<USCI> {
             let statw = usci.statw_rd();
             let data = usci.rx_rd();
 
-            if statw.ucfe() {
+            if statw.ucbrk() {
+                Err(nb::Error::Other(RecvError::Break))
+            } else if statw.ucfe() {
                 Err(nb::Error::Other(RecvError::Framing))
             } else if statw.ucpe() {
                 Err(nb::Error::Other(RecvError::Parity))
@@ -563,3 +884,254 @@ impl<USCI: SerialUsci> Read<u8> for Rx<USCI> {
         }
     }
 }
+
+/// Interrupt-driven transmitter that sends bytes from a ring buffer.
+///
+/// `write()` queues bytes and enables the Tx interrupt, and the UART's ISR must call
+/// `on_interrupt()` to send each byte. Since both the ISR and the main code need `&mut` access,
+/// share it between them through an interrupt-safe cell such as a `Mutex<RefCell<_>>` or an RTIC
+/// resource, which provides the critical section that keeps the buffer consistent.
+///
+/// The queue only drains from the ISR, so never wait for space or for `flush()` while holding the
+/// lock. Release it between retries of a `WouldBlock` result, otherwise the ISR can't run and the
+/// wait never ends. For the same reason, `BufferedTx` doesn't implement the blocking `Write`
+/// traits.
+pub struct BufferedTx<USCI: SerialUsci> {
+    tx: Tx<USCI>,
+    buffer: &'static mut [u8],
+    head: usize,
+    len: usize,
+}
+
+impl<USCI: SerialUsci> BufferedTx<USCI> {
+    /// Wrap a Tx pin with a ring buffer
+    #[inline]
+    pub fn new(tx: Tx<USCI>, buffer: &'static mut [u8]) -> Self {
+        BufferedTx {
+            tx,
+            buffer,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Queue as many bytes of `data` as fit in the buffer, returning how many were queued
+    pub fn write(&mut self, data: &[u8]) -> usize {
+        let cap = self.buffer.len();
+        let count = (cap - self.len).min(data.len());
+        for &byte in &data[..count] {
+            let tail = (self.head + self.len) % cap;
+            self.buffer[tail] = byte;
+            self.len += 1;
+        }
+        if self.len > 0 {
+            self.tx.enable_tx_interrupts();
+        }
+        count
+    }
+
+    /// Send the next queued byte if the Tx buffer is free. Call this from the UART's ISR. The Tx
+    /// interrupt is disabled once the queue is empty, and enabled again by `write()`.
+    pub fn on_interrupt(&mut self) {
+        let usci = unsafe { USCI::steal() };
+        if self.len > 0 && usci.txifg_rd() {
+            usci.tx_wr(self.buffer[self.head]);
+            self.head = (self.head + 1) % self.buffer.len();
+            self.len -= 1;
+        }
+        if self.len == 0 {
+            self.tx.disable_tx_interrupts();
+        }
+    }
+
+    /// Number of bytes waiting to be sent
+    #[inline]
+    pub fn queued(&self) -> usize {
+        self.len
+    }
+
+    /// Stop sending and give back the Tx pin and buffer. Bytes still in the queue are discarded.
+    #[inline]
+    pub fn free(mut self) -> (Tx<USCI>, &'static mut [u8]) {
+        self.tx.disable_tx_interrupts();
+        (self.tx, self.buffer)
+    }
+}
+
+impl<USCI: SerialUsci> Write<u8> for BufferedTx<USCI> {
+    type Error = void::Void;
+
+    /// Returns `WouldBlock` until every queued byte has been moved into the Tx buffer and the Tx
+    /// buffer is empty
+    #[inline]
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        if self.len == 0 {
+            self.tx.flush()
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Queue a single byte, returning `WouldBlock` if the queue is full
+    #[inline]
+    fn write(&mut self, data: u8) -> nb::Result<(), Self::Error> {
+        if BufferedTx::write(self, &[data]) == 1 {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+/// Interrupt-driven receiver that stores received bytes in a ring buffer.
+///
+/// The Rx interrupt is enabled on creation, and the UART's ISR must call `on_interrupt()` to store
+/// each byte. As with `BufferedTx`, share it between the ISR and the main code through an
+/// interrupt-safe cell.
+pub struct BufferedRx<USCI: SerialUsci> {
+    rx: Rx<USCI>,
+    buffer: &'static mut [u8],
+    head: usize,
+    len: usize,
+    error: Option<RecvError>,
+}
+
+impl<USCI: SerialUsci> BufferedRx<USCI> {
+    /// Wrap an Rx pin with a ring buffer and enable Rx interrupts
+    #[inline]
+    pub fn new(mut rx: Rx<USCI>, buffer: &'static mut [u8]) -> Self {
+        rx.enable_rx_interrupts();
+        BufferedRx {
+            rx,
+            buffer,
+            head: 0,
+            len: 0,
+            error: None,
+        }
+    }
+
+    /// Store the received byte in the buffer. Call this from the UART's ISR.
+    ///
+    /// If the buffer is full the new byte is dropped and reported as an overrun. Receive errors
+    /// are held until the next read, and only the latest error is kept.
+    pub fn on_interrupt(&mut self) {
+        let cap = self.buffer.len();
+        match self.rx.read() {
+            Ok(byte) if self.len < cap => {
+                let tail = (self.head + self.len) % cap;
+                self.buffer[tail] = byte;
+                self.len += 1;
+            }
+            Ok(byte) => self.error = Some(RecvError::Overrun(byte)),
+            Err(nb::Error::Other(err)) => self.error = Some(err),
+            Err(nb::Error::WouldBlock) => (),
+        }
+    }
+
+    /// Move the oldest buffered bytes into `out`, returning how many were copied. A pending
+    /// receive error is returned instead, and cleared, before any more bytes are read.
+    pub fn read(&mut self, out: &mut [u8]) -> Result<usize, RecvError> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        let count = self.len.min(out.len());
+        let cap = self.buffer.len();
+        for slot in out.iter_mut().take(count) {
+            *slot = self.buffer[self.head];
+            self.head = (self.head + 1) % cap;
+        }
+        self.len -= count;
+        Ok(count)
+    }
+
+    /// Number of bytes waiting in the buffer
+    #[inline]
+    pub fn available(&self) -> usize {
+        self.len
+    }
+
+    /// Stop receiving and give back the Rx pin and buffer. Bytes still in the buffer are
+    /// discarded.
+    #[inline]
+    pub fn free(mut self) -> (Rx<USCI>, &'static mut [u8]) {
+        self.rx.disable_rx_interrupts();
+        (self.rx, self.buffer)
+    }
+}
+
+impl<USCI: SerialUsci> Read<u8> for BufferedRx<USCI> {
+    type Error = RecvError;
+
+    /// Pop the oldest buffered byte, or return a pending receive error
+    #[inline]
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let mut byte = [0];
+        match BufferedRx::read(self, &mut byte) {
+            Ok(1) => Ok(byte[0]),
+            Ok(_) => Err(nb::Error::WouldBlock),
+            Err(err) => Err(nb::Error::Other(err)),
+        }
+    }
+}
+
+/// Serial transmitter and receiver combined into one object, for when both directions are owned
+/// by the same code. Use `split()` to separate them again, e.g. for interrupt-driven operation.
+pub struct Serial<USCI: SerialUsci> {
+    tx: Tx<USCI>,
+    rx: Rx<USCI>,
+}
+
+impl<USCI: SerialUsci> Serial<USCI> {
+    /// Combine the Tx and Rx pins of the same UART
+    #[inline(always)]
+    pub fn new(tx: Tx<USCI>, rx: Rx<USCI>) -> Self {
+        Serial { tx, rx }
+    }
+
+    /// Split into separate Tx and Rx pins
+    #[inline(always)]
+    pub fn split(self) -> (Tx<USCI>, Rx<USCI>) {
+        (self.tx, self.rx)
+    }
+
+    /// Change the parity and number of stop bits at runtime, keeping the baud rate, clock source
+    /// and interrupt enables. The UART is briefly reset, so any byte being sent or received is
+    /// lost.
+    #[inline]
+    pub fn set_framing(&mut self, parity: Parity, stopbits: StopBits) {
+        set_framing::<USCI>(parity, stopbits);
+    }
+
+    /// Read the UART interrupt vector, which reports the highest priority pending interrupt.
+    /// Reading the vector automatically clears the corresponding interrupt flag, though for
+    /// `RxReady` the received byte must still be read.
+    #[inline]
+    pub fn interrupt_source(&mut self) -> UartVector {
+        read_uart_vector::<USCI>()
+    }
+}
+
+impl<USCI: SerialUsci> Read<u8> for Serial<USCI> {
+    type Error = RecvError;
+
+    #[inline(always)]
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        self.rx.read()
+    }
+}
+
+impl<USCI: SerialUsci> Write<u8> for Serial<USCI> {
+    type Error = void::Void;
+
+    #[inline(always)]
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.tx.flush()
+    }
+
+    #[inline(always)]
+    fn write(&mut self, data: u8) -> nb::Result<(), Self::Error> {
+        self.tx.write(data)
+    }
+}
+
+impl<USCI: SerialUsci> embedded_hal::blocking::serial::write::Default<u8> for Serial<USCI> {}
@@ -7,6 +7,19 @@
 //! `SpiBus` implements the embedded_hal `FullDuplex` trait with non-blocking `.read()` and `.send()` methods, 
 //! and the blocking embedded_hal `Transfer` and `Write` traits, with `.transfer()`  and `.write()` methods respectively.
 //!
+//! The SPI mode can be given either as an embedded_hal `Mode` (the `MODE_0` to `MODE_3` constants are re-exported here),
+//! or via `spi_mode()` using the mode number found in most device datasheets.
+//!
+//! `SpiBus::start_transfer()` starts an interrupt-driven block transfer, which the eUSCI's ISR advances one byte at a time,
+//! so the CPU can sleep between bytes.
+//!
+//! For slaves that expect chip select to toggle around every word, `SpiBusConfig::auto_cs_per_transaction()` returns an `SpiWordBus`
+//! that drives the STE pin in hardware and only offers word-at-a-time transfers.
+//!
+//! A bus that talks to a single device can take ownership of its chip select pin with `SpiBus::with_cs()`.
+//! To share one bus between several devices, put the `SpiBus` in a `RefCell` and give each device its own `SpiDevice` handle,
+//! which owns the device's chip select pin and SPI mode.
+//!
 //! Pins used:
 //!
 //! eUSCI_A0: {MISO: `P1.7`, MOSI: `P1.6`, SCLK: `P1.5`}. `P1.4` can optionally used as a hardware-controlled chip select pin.
@@ -22,11 +35,31 @@ use crate::{
     gpio::{Alternate1, Pin, Pin0, Pin1, Pin2, Pin3, Pin4, Pin5, Pin6, Pin7, P1, P4},
     hw_traits::eusci::{EusciSPI, Ucmode, Ucssel, UcxSpiCtw0},
 };
+use core::cell::{Cell, RefCell};
 use core::marker::PhantomData;
+use embedded_hal::digital::v2::OutputPin;
 use embedded_hal::spi::FullDuplex;
 use msp430fr2355 as pac;
 use nb::Error::WouldBlock;
 
+pub use crate::hal::spi::{MODE_0, MODE_1, MODE_2, MODE_3};
+
+/// Convert a datasheet SPI mode number into a `Mode`. Mode 0 idles low and captures on the first edge,
+/// mode 1 idles low and captures on the second edge, mode 2 idles high and captures on the first edge,
+/// and mode 3 idles high and captures on the second edge.
+///
+/// # Panics
+/// Panics if `mode` is greater than 3.
+pub const fn spi_mode(mode: u8) -> Mode {
+    match mode {
+        0 => MODE_0,
+        1 => MODE_1,
+        2 => MODE_2,
+        3 => MODE_3,
+        _ => panic!("SPI mode must be between 0 and 3"),
+    }
+}
+
 /// Marks a eUSCI capable of SPI communication (in this case, all euscis do)
 pub trait SpiUsci: EusciSPI {
     /// Master In Slave Out (refered to as SOMI in datasheet)
@@ -170,7 +203,7 @@ impl<USCI: SpiUsci> SpiBusConfig<USCI, NoClockSet> {
                 Polarity::IdleHigh => true,
             },
             ucmsb: msb_first,
-            uc7bit: false,
+            uc7bit: false, // overwritten by `seven_bit_words()`
             ucmst: true,
             ucsync: true,
             ucstem: true,
@@ -203,6 +236,19 @@ impl<USCI: SpiUsci> SpiBusConfig<USCI, NoClockSet> {
         SpiBusConfig { usci: self.usci, prescaler: self.prescaler, ctlw0: self.ctlw0, _phantom: PhantomData }
     }
 }
+
+impl<USCI: SpiUsci, STATE> SpiBusConfig<USCI, STATE> {
+    /// Use 7-bit words instead of 8-bit words, for older devices that expect them. 
+    /// 
+    /// Only the lower 7 bits of each byte are sent, and received bytes are right-justified with the top bit always cleared.
+    /// With MSB-first bit order the first bit sent is bit 6.
+    #[inline]
+    pub fn seven_bit_words(mut self) -> Self {
+        self.ctlw0.uc7bit = true;
+        self
+    }
+}
+
 #[allow(private_bounds)]
 impl<USCI: SpiUsci> SpiBusConfig<USCI, ClockSet> {
     /// Performs hardware configuration and creates an SPI bus. The STE pin is used as an automatically controlled chip select pin. Suitable for systems with only one slave device.
@@ -240,6 +286,31 @@ impl<USCI: SpiUsci> SpiBusConfig<USCI, ClockSet> {
         SpiBus(PhantomData)
     }
 
+    /// Performs hardware configuration and creates an SPI bus whose STE pin is driven as a chip select by the hardware,
+    /// asserted (low) only while a word is being shifted out. Suitable for simple slaves such as ADCs and DACs that expect
+    /// chip select to toggle around every word.
+    /// 
+    /// Chip select is released as soon as the eUSCI goes idle, so multi-byte transactions can't hold it asserted.
+    /// The returned `SpiWordBus` therefore only offers word-at-a-time transfers, each of which waits for the previous word to finish.
+    #[inline(always)]
+    pub fn auto_cs_per_transaction<
+        SO: Into<USCI::MISO>,
+        SI: Into<USCI::MOSI>,
+        CLK: Into<USCI::SCLK>,
+        STE: Into<USCI::STE>,
+    >(
+        &mut self,
+        _miso: SO,
+        _mosi: SI,
+        _sclk: CLK,
+        _cs: STE,
+    ) -> SpiWordBus<USCI> {
+        self.ctlw0.ucmode = Ucmode::FourPinSPI0;
+        self.ctlw0.ucstem = true;
+        self.configure_hw();
+        SpiWordBus(PhantomData)
+    }
+
     #[inline]
     fn configure_hw(&self) {
         self.usci.ctw0_set_rst();
@@ -313,6 +384,147 @@ impl<USCI: SpiUsci> SpiBus<USCI> {
         usci.set_spi_mode(mode);
         usci.ctw0_clear_rst();
     }
+
+    #[inline(always)]
+    /// Change the bit order. The SPI mode, clock source and prescaler are preserved.
+    /// 
+    /// The peripheral is briefly held in reset, so call this between transactions. Interrupt enables are restored afterwards.
+    pub fn set_bit_order(&mut self, msb_first: bool) {
+        let usci = unsafe { USCI::steal() };
+        let ie = usci.ie_rd();
+        usci.ctw0_set_rst();
+        usci.set_msb_first(msb_first);
+        usci.ctw0_clear_rst();
+        usci.ie_wr(ie);
+    }
+
+    /// Fill `buffer` with bytes read from the bus, sending `0x00` to generate the clock.
+    /// Like the blocking `Write` and `Transfer` implementations, with a prescaler of 16 or more the next dummy byte is loaded 
+    /// while the current byte is still being received, so long reads (e.g. from an SPI ADC) run back-to-back without idling 
+    /// between bytes.
+    pub fn read_into(&mut self, buffer: &mut [u8]) -> Result<(), SPIErr> {
+        self.blocking_transfer(buffer.len(), |_| 0x00, |idx, byte| buffer[idx] = byte)
+    }
+
+    /// Give this bus exclusive use of a chip select pin, which is deasserted (set high) immediately.
+    /// The returned `SpiWithCs` asserts chip select around every blocking transfer or write.
+    pub fn with_cs<CS: OutputPin>(self, mut cs: CS) -> Result<SpiWithCs<USCI, CS>, CS::Error> {
+        cs.set_high()?;
+        Ok(SpiWithCs { bus: self, cs })
+    }
+
+    /// Start an interrupt-driven block transfer, which sends `tx` while filling `rx` with the received bytes.
+    /// The transfer is as long as the longer of the two buffers. If `tx` is shorter, `0xFF` is sent for the remaining bytes.
+    /// If `rx` is shorter, the remaining received bytes are discarded.
+    /// 
+    /// Rx interrupts are enabled for the duration of the transfer, and the eUSCI's ISR must call `SpiTransfer::on_interrupt()`
+    /// to advance it by one byte. Once `SpiTransfer::is_done()` returns true, call `SpiTransfer::finish()` to get the bus and buffers back.
+    pub fn start_transfer(self, tx: &'static [u8], rx: &'static mut [u8]) -> SpiTransfer<USCI> {
+        let usci = unsafe { USCI::steal() };
+        let len = tx.len().max(rx.len());
+        let mut transfer = SpiTransfer { bus: self, tx, rx, len, sent: 0, received: 0 };
+        if len > 0 {
+            // Flush any stale byte so the first Rx interrupt belongs to this transfer
+            usci.rxbuf_rd();
+            transfer.send_next(&usci);
+            usci.set_receive_interrupt();
+        }
+        transfer
+    }
+}
+
+/// An SPI bus that asserts its hardware chip select around every word, created by `SpiBusConfig::auto_cs_per_transaction()`.
+/// 
+/// Unlike `SpiBus`, the next word is only sent once the previous one has been received, so chip select is released between words.
+pub struct SpiWordBus<USCI: SpiUsci>(PhantomData<USCI>);
+
+impl<USCI: SpiUsci> SpiWordBus<USCI> {
+    /// Send one word and return the word received in reply, blocking until the transfer completes
+    pub fn transfer_word(&mut self, word: u8) -> Result<u8, SPIErr> {
+        let usci = unsafe { USCI::steal() };
+        while !usci.transmit_flag() {}
+        usci.txbuf_wr(word);
+        while !usci.receive_flag() {}
+        if usci.overrun_flag() {
+            Err(SPIErr::OverrunError(usci.rxbuf_rd()))
+        } else {
+            Ok(usci.rxbuf_rd())
+        }
+    }
+
+    /// Send one word, discarding the reply, blocking until the transfer completes
+    #[inline]
+    pub fn write_word(&mut self, word: u8) -> Result<(), SPIErr> {
+        self.transfer_word(word).map(|_| ())
+    }
+
+    #[inline(always)]
+    /// Change the SPI mode
+    pub fn change_mode(&mut self, mode: Mode) {
+        let usci = unsafe { USCI::steal() };
+        usci.ctw0_set_rst();
+        usci.set_spi_mode(mode);
+        usci.ctw0_clear_rst();
+    }
+}
+
+/// An interrupt-driven SPI block transfer, created by `SpiBus::start_transfer()`.
+/// 
+/// Only one byte is ever in flight, since the next byte is sent only after the previous one has been received, so overruns can't occur.
+pub struct SpiTransfer<USCI: SpiUsci> {
+    bus: SpiBus<USCI>,
+    tx: &'static [u8],
+    rx: &'static mut [u8],
+    len: usize,
+    sent: usize,
+    received: usize,
+}
+
+impl<USCI: SpiUsci> SpiTransfer<USCI> {
+    #[inline(always)]
+    fn send_next(&mut self, usci: &USCI) {
+        usci.txbuf_wr(self.tx.get(self.sent).copied().unwrap_or(0xFF));
+        self.sent += 1;
+    }
+
+    /// Advance the transfer by storing the byte just received and sending the next one. Call this from the eUSCI's ISR.
+    /// Does nothing if no byte has been received, or the transfer has already finished.
+    pub fn on_interrupt(&mut self) {
+        let usci = unsafe { USCI::steal() };
+        if self.is_done() || !usci.receive_flag() {
+            return;
+        }
+        let byte = usci.rxbuf_rd();
+        if let Some(slot) = self.rx.get_mut(self.received) {
+            *slot = byte;
+        }
+        self.received += 1;
+        if self.sent < self.len {
+            self.send_next(&usci);
+        } else {
+            usci.clear_receive_interrupt();
+        }
+    }
+
+    /// Whether every byte has been sent and received
+    #[inline(always)]
+    pub fn is_done(&self) -> bool {
+        self.received == self.len
+    }
+
+    /// Number of bytes received so far
+    #[inline(always)]
+    pub fn bytes_received(&self) -> usize {
+        self.received
+    }
+
+    /// Abort the transfer if it's still in progress, and return the bus and buffers. 
+    /// When aborted, `rx` only holds the first `bytes_received()` bytes of the reply.
+    pub fn finish(self) -> (SpiBus<USCI>, &'static [u8], &'static mut [u8]) {
+        let usci = unsafe { USCI::steal() };
+        usci.clear_receive_interrupt();
+        (self.bus, self.tx, self.rx)
+    }
 }
 
 /// SPI transmit/receive errors
@@ -320,6 +532,10 @@ impl<USCI: SpiUsci> SpiBus<USCI> {
 #[non_exhaustive]
 pub enum SPIErr {
     /// Data in the recieve buffer was overwritten before it was read. The contained data is the new contents of the recieve buffer.
+    /// 
+    /// With a prescaler of 16 or more, the blocking transfers load the next byte before reading the reply to the current one,
+    /// with interrupts disabled. They only overrun if BRCLK is faster than MCLK, and wait for the bus to go idle and empty 
+    /// the receive buffer before returning this error. Smaller prescalers send one byte at a time and never overrun.
     OverrunError(u8),
     // In future the framing error bit UCFE may appear here. Right now it's unimplemented.
 }
@@ -352,6 +568,178 @@ impl<USCI: SpiUsci> FullDuplex<u8> for SpiBus<USCI> {
     }
 }
 
-// Implementing FullDuplex above gets us a blocking write and transfer implementation for free
-impl<USCI: SpiUsci> embedded_hal::blocking::spi::write::Default<u8> for SpiBus<USCI> {}
-impl<USCI: SpiUsci> embedded_hal::blocking::spi::transfer::Default<u8> for SpiBus<USCI> {}
+// Smallest prescaler at which the blocking transfers preload the next byte. Each byte then lasts at least
+// 128 BRCLK cycles, which leaves the CPU enough time to read every reply as long as BRCLK isn't faster than MCLK.
+const PRELOAD_MIN_PRESCALER: u16 = 16;
+
+impl<USCI: SpiUsci> SpiBus<USCI> {
+    // Shift out `len` bytes, with `tx(idx)` giving each byte to send and `rx(idx, byte)` taking each reply.
+    // 
+    // At small prescalers the CPU can't keep up with preloading, so each byte is only sent once the reply to 
+    // the previous one has been read.
+    fn blocking_transfer(&mut self, len: usize, mut tx: impl FnMut(usize) -> u8, mut rx: impl FnMut(usize, u8)) -> Result<(), SPIErr> {
+        let usci = unsafe { USCI::steal() };
+        if usci.brw_rd() < PRELOAD_MIN_PRESCALER {
+            for idx in 0..len {
+                nb::block!(self.send(tx(idx)))?;
+                rx(idx, nb::block!(self.read())?);
+            }
+            return Ok(());
+        }
+        // An interrupt mid-transfer could hold up the reads for long enough to overrun
+        msp430::interrupt::free(|_| self.preloaded_transfer(len, tx, rx))
+    }
+
+    // Takes advantage of the double-buffered Tx buffer: the next byte is loaded as soon as the buffer empties, 
+    // so it's shifted out back-to-back with the previous byte while the reply to that previous byte is being read. 
+    // This keeps the bus busy instead of idling between bytes.
+    // 
+    // The reply to each byte must be read before the next one finishes shifting in. Should the CPU still fall behind,
+    // the eUSCI overruns and the transfer is abandoned with an `OverrunError`.
+    fn preloaded_transfer(&mut self, len: usize, mut tx: impl FnMut(usize) -> u8, mut rx: impl FnMut(usize, u8)) -> Result<(), SPIErr> {
+        if len == 0 {
+            return Ok(());
+        }
+        nb::block!(self.send(tx(0)))?;
+        for idx in 0..len {
+            if idx + 1 < len {
+                nb::block!(self.send(tx(idx + 1)))?;
+            }
+            match nb::block!(self.read()) {
+                Ok(byte) => rx(idx, byte),
+                Err(err) => {
+                    self.drain();
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Wait for any byte still in flight to finish, then discard it. Reading RXBUF also clears RXIFG and UCOE, 
+    // so the next transfer starts cleanly.
+    fn drain(&mut self) {
+        let usci = unsafe { USCI::steal() };
+        while usci.busy_flag() {}
+        if usci.receive_flag() {
+            usci.rxbuf_rd();
+        }
+    }
+}
+
+impl<USCI: SpiUsci> embedded_hal::blocking::spi::Transfer<u8> for SpiBus<USCI> {
+    type Error = SPIErr;
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        // Replies overwrite the buffer one byte behind the byte being sent
+        let cells = Cell::from_mut(&mut *words).as_slice_of_cells();
+        self.blocking_transfer(cells.len(), |idx| cells[idx].get(), |idx, byte| cells[idx].set(byte))?;
+        Ok(words)
+    }
+}
+
+impl<USCI: SpiUsci> embedded_hal::blocking::spi::Write<u8> for SpiBus<USCI> {
+    type Error = SPIErr;
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.blocking_transfer(words.len(), |idx| words[idx], |_, _| ())
+    }
+}
+
+/// Handle to an SPI bus shared between several devices, each with its own chip select pin and SPI mode.
+/// 
+/// Each transaction borrows the bus, switches it to this device's mode, then asserts chip select for the duration of the transfer.
+/// Using a handle while another transaction on the same bus is in progress, such as from an interrupt, panics.
+pub struct SpiDevice<'a, USCI: SpiUsci, CS: OutputPin> {
+    bus: &'a RefCell<SpiBus<USCI>>,
+    cs: CS,
+    mode: Mode,
+}
+
+/// Errors from a transaction on a shared SPI bus
+#[derive(Clone, Copy, Debug)]
+pub enum SpiDeviceErr<E> {
+    /// Error from the SPI bus
+    Spi(SPIErr),
+    /// Error from setting the chip select pin
+    ChipSelect(E),
+}
+
+impl<'a, USCI: SpiUsci, CS: OutputPin> SpiDevice<'a, USCI, CS> {
+    /// Create a new handle to a shared SPI bus. The chip select pin is deasserted (set high) immediately.
+    pub fn new(bus: &'a RefCell<SpiBus<USCI>>, mut cs: CS, mode: Mode) -> Result<Self, CS::Error> {
+        cs.set_high()?;
+        Ok(Self { bus, cs, mode })
+    }
+
+    /// Release the chip select pin
+    pub fn free(self) -> CS {
+        self.cs
+    }
+
+    fn transaction<R>(&mut self, f: impl FnOnce(&mut SpiBus<USCI>) -> Result<R, SPIErr>) -> Result<R, SpiDeviceErr<CS::Error>> {
+        let mut bus = self.bus.borrow_mut();
+        bus.change_mode(self.mode);
+        self.cs.set_low().map_err(SpiDeviceErr::ChipSelect)?;
+        // The blocking loops only return once the reply to the last byte has been received, so the bus is idle by now
+        let res = f(&mut bus);
+        self.cs.set_high().map_err(SpiDeviceErr::ChipSelect)?;
+        res.map_err(SpiDeviceErr::Spi)
+    }
+}
+
+impl<'a, USCI: SpiUsci, CS: OutputPin> embedded_hal::blocking::spi::Transfer<u8> for SpiDevice<'a, USCI, CS> {
+    type Error = SpiDeviceErr<CS::Error>;
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        use embedded_hal::blocking::spi::Transfer;
+        self.transaction(|bus| bus.transfer(&mut *words).map(|_| ()))?;
+        Ok(words)
+    }
+}
+
+impl<'a, USCI: SpiUsci, CS: OutputPin> embedded_hal::blocking::spi::Write<u8> for SpiDevice<'a, USCI, CS> {
+    type Error = SpiDeviceErr<CS::Error>;
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        use embedded_hal::blocking::spi::Write;
+        self.transaction(|bus| bus.write(words))
+    }
+}
+
+/// An SPI bus that owns a single device's chip select pin, created by `SpiBus::with_cs()`.
+/// 
+/// Each blocking transfer or write asserts chip select for its duration. If the bus reports an error partway through, 
+/// chip select is still deasserted before the error is returned. Use `SpiDevice` instead to share a bus between several devices.
+pub struct SpiWithCs<USCI: SpiUsci, CS: OutputPin> {
+    bus: SpiBus<USCI>,
+    cs: CS,
+}
+
+impl<USCI: SpiUsci, CS: OutputPin> SpiWithCs<USCI, CS> {
+    /// Release the bus and the chip select pin
+    pub fn free(self) -> (SpiBus<USCI>, CS) {
+        (self.bus, self.cs)
+    }
+
+    fn transaction<R>(&mut self, f: impl FnOnce(&mut SpiBus<USCI>) -> Result<R, SPIErr>) -> Result<R, SpiDeviceErr<CS::Error>> {
+        self.cs.set_low().map_err(SpiDeviceErr::ChipSelect)?;
+        // The blocking loops only return once the reply to the last byte has been received, so the bus is idle by now
+        let res = f(&mut self.bus);
+        self.cs.set_high().map_err(SpiDeviceErr::ChipSelect)?;
+        res.map_err(SpiDeviceErr::Spi)
+    }
+}
+
+impl<USCI: SpiUsci, CS: OutputPin> embedded_hal::blocking::spi::Transfer<u8> for SpiWithCs<USCI, CS> {
+    type Error = SpiDeviceErr<CS::Error>;
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        use embedded_hal::blocking::spi::Transfer;
+        self.transaction(|bus| bus.transfer(&mut *words).map(|_| ()))?;
+        Ok(words)
+    }
+}
+
+impl<USCI: SpiUsci, CS: OutputPin> embedded_hal::blocking::spi::Write<u8> for SpiWithCs<USCI, CS> {
+    type Error = SpiDeviceErr<CS::Error>;
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        use embedded_hal::blocking::spi::Write;
+        self.transaction(|bus| bus.write(words))
+    }
+}
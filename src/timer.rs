@@ -2,12 +2,13 @@
 //!
 //! Configures the board's TimerB peripherals into periodic countdown timers. Each peripheral
 //! consists of a main timer and multiple "sub-timers". Sub-timers have their own thresholds and
-//! interrupts but share their countdowns with their main timer.
+//! interrupts but share their countdowns with their main timer. Sub-timers can also be turned
+//! into periodic `Alarm`s, which reload themselves after each expiry.
 //!
 //! This module also contains traits used by other HAL modules that depend on TimerB, such as
 //! `Capture` and `Pwm`.
 
-use crate::clock::{Aclk, Smclk};
+use crate::clock::{Aclk, Clock, Smclk};
 use crate::gpio::{Alternate1, Floating, Input, Pin, Pin2, Pin6, Pin7, P2, P5, P6};
 use crate::hw_traits::timerb::{CCRn, Tbssel, TimerB};
 use core::marker::PhantomData;
@@ -68,9 +69,13 @@ impl CapCmpTimer7 for pac::TB3 {}
 /// Configuration object for the TimerB peripheral
 ///
 /// Used to configure `Timer`, `Capture`, and `Pwm`, which all use the TimerB peripheral.
+///
+/// Selecting ACLK or SMCLK requires the corresponding clock object, proving that the clock is
+/// running, and allows the resulting tick frequency to be queried with `tick_freq()`.
 pub struct TimerConfig<T: TimerPeriph> {
     _timer: PhantomData<T>,
     sel: Tbssel,
+    src_freq: Option<u32>,
     div: TimerDiv,
     ex_div: TimerExDiv,
 }
@@ -78,10 +83,11 @@ pub struct TimerConfig<T: TimerPeriph> {
 impl<T: TimerPeriph> TimerConfig<T> {
     /// Configure timer clock source to ACLK
     #[inline]
-    pub fn aclk(_aclk: &Aclk) -> Self {
+    pub fn aclk(aclk: &Aclk) -> Self {
         TimerConfig {
             _timer: PhantomData,
             sel: Tbssel::Aclk,
+            src_freq: Some(aclk.freq() as u32),
             div: TimerDiv::_1,
             ex_div: TimerExDiv::_1,
         }
@@ -89,21 +95,24 @@ impl<T: TimerPeriph> TimerConfig<T> {
 
     /// Configure timer clock source to SMCLK
     #[inline]
-    pub fn smclk(_smclk: &Smclk) -> Self {
+    pub fn smclk(smclk: &Smclk) -> Self {
         TimerConfig {
             _timer: PhantomData,
             sel: Tbssel::Smclk,
+            src_freq: Some(smclk.freq()),
             div: TimerDiv::_1,
             ex_div: TimerExDiv::_1,
         }
     }
 
-    /// Configure timer clock source to TBCLK
+    /// Configure timer clock source to TBCLK. Since the frequency of the external clock is
+    /// unknown, `tick_freq()` will return `None`.
     #[inline]
     pub fn tbclk(_pin: T::Tbxclk) -> Self {
         TimerConfig {
             _timer: PhantomData,
             sel: Tbssel::Tbxclk,
+            src_freq: None,
             div: TimerDiv::_1,
             ex_div: TimerExDiv::_1,
         }
@@ -115,11 +124,35 @@ impl<T: TimerPeriph> TimerConfig<T> {
         TimerConfig {
             _timer: PhantomData,
             sel: self.sel,
+            src_freq: self.src_freq,
             div,
             ex_div,
         }
     }
 
+    /// Frequency at which the timer counts, in Hz, after both clock dividers are applied.
+    /// Returns `None` if the timer is clocked from the external TBCLK pin.
+    #[inline]
+    pub fn tick_freq(&self) -> Option<u32> {
+        let div = match self.div {
+            TimerDiv::_1 => 1,
+            TimerDiv::_2 => 2,
+            TimerDiv::_4 => 4,
+            TimerDiv::_8 => 8,
+        };
+        let ex_div = match self.ex_div {
+            TimerExDiv::_1 => 1,
+            TimerExDiv::_2 => 2,
+            TimerExDiv::_3 => 3,
+            TimerExDiv::_4 => 4,
+            TimerExDiv::_5 => 5,
+            TimerExDiv::_6 => 6,
+            TimerExDiv::_7 => 7,
+            TimerExDiv::_8 => 8,
+        };
+        self.src_freq.map(|freq| freq / (div * ex_div))
+    }
+
     #[inline]
     pub(crate) fn write_regs(self, timer: &T) {
         timer.reset();
@@ -310,6 +343,31 @@ impl<T: TimerPeriph> Timer<T> {
         let timer = unsafe { T::steal() };
         timer.tbie_clr();
     }
+
+    /// Pause the timer, keeping the current count and threshold. Sub-timers share the main
+    /// timer's count, so they're paused as well.
+    #[inline(always)]
+    pub fn stop(&mut self) {
+        let timer = unsafe { T::steal() };
+        timer.stop();
+    }
+
+    /// Continue counting from where `stop()` paused the timer. Does nothing if the timer is
+    /// already running.
+    #[inline]
+    pub fn resume(&mut self) {
+        let timer = unsafe { T::steal() };
+        if timer.is_stopped() {
+            timer.resume_upmode();
+        }
+    }
+
+    /// Reset the count to zero without changing the threshold or whether the timer is running
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        let timer = unsafe { T::steal() };
+        timer.reset();
+    }
 }
 
 impl<T: CapCmp<C>, C> SubTimer<T, C> {
@@ -350,3 +408,99 @@ impl<T: CapCmp<C>, C> SubTimer<T, C> {
         timer.ccie_clr();
     }
 }
+
+impl<T: TimerPeriph + CapCmp<C>, C> SubTimer<T, C> {
+    /// Turn the sub-timer into a periodic alarm that fires every `period` ticks, starting
+    /// `period` ticks from now. Several alarms with different periods can share one main timer.
+    ///
+    /// The main timer acts as the timebase for all its alarms, so it should be running with a
+    /// threshold at least as large as `period`. Starting it with `u16::MAX` makes it free-running.
+    #[inline]
+    pub fn into_alarm(self, period: u16) -> Alarm<T, C> {
+        let alarm = Alarm {
+            period,
+            _timer: PhantomData,
+            _ccr: PhantomData,
+        };
+        let timer = unsafe { T::steal() };
+        CCRn::<C>::set_ccrn(&timer, alarm.next_count(timer.tbxr_rd()));
+        CCRn::<C>::ccifg_clr(&timer);
+        alarm
+    }
+}
+
+/// Periodic alarm derived from a sub-timer
+///
+/// After each expiry the sub-timer threshold is automatically moved forward by one period,
+/// wrapping around at the main timer threshold, so the alarm keeps firing at a fixed rate without
+/// drifting.
+pub struct Alarm<T: CapCmp<C>, C> {
+    period: u16,
+    _timer: PhantomData<T>,
+    _ccr: PhantomData<C>,
+}
+
+impl<T: TimerPeriph + CapCmp<C>, C> Alarm<T, C> {
+    #[inline]
+    fn next_count(&self, from: u16) -> u16 {
+        let timer = unsafe { T::steal() };
+        let top = CCRn::<CCR0>::get_ccrn(&timer) as u32 + 1;
+        ((from as u32 + self.period as u32) % top) as u16
+    }
+
+    /// Check whether the alarm has fired since the last call. If it has, the next expiry is
+    /// scheduled one period after the previous one.
+    #[inline]
+    pub fn poll(&mut self) -> nb::Result<(), void::Void> {
+        let timer = unsafe { T::steal() };
+        if CCRn::<C>::ccifg_rd(&timer) {
+            CCRn::<C>::ccifg_clr(&timer);
+            self.reload();
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Schedule the next expiry one period after the previous one. Use this in an ISR after
+    /// `TBxIV::interrupt_vector()` reports this sub-timer, since reading the vector already clears
+    /// the interrupt flag that `poll()` checks.
+    #[inline]
+    pub fn reload(&mut self) {
+        let timer = unsafe { T::steal() };
+        let next = self.next_count(CCRn::<C>::get_ccrn(&timer));
+        CCRn::<C>::set_ccrn(&timer, next);
+    }
+
+    /// Change the alarm period. Takes effect after the next expiry.
+    #[inline]
+    pub fn set_period(&mut self, period: u16) {
+        self.period = period;
+    }
+
+    /// Current alarm period, in timer ticks
+    #[inline]
+    pub fn period(&self) -> u16 {
+        self.period
+    }
+
+    #[inline(always)]
+    /// Enable the alarm interrupts
+    pub fn enable_interrupts(&mut self) {
+        let timer = unsafe { T::steal() };
+        CCRn::<C>::ccie_set(&timer);
+    }
+
+    #[inline(always)]
+    /// Disable the alarm interrupts
+    pub fn disable_interrupts(&mut self) {
+        let timer = unsafe { T::steal() };
+        CCRn::<C>::ccie_clr(&timer);
+    }
+
+    /// Turn the alarm back into a plain sub-timer
+    #[inline]
+    pub fn free(self) -> SubTimer<T, C> {
+        SubTimer::new()
+    }
+}
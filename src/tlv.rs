@@ -0,0 +1,121 @@
+//! Device descriptor (TLV) table
+//!
+//! The device descriptor table is a read-only region of information memory written at the
+//! factory. It's organized as a sequence of tag-length-value entries, which hold things like the
+//! die record and calibration constants for the ADC and voltage reference.
+//!
+//! Use `find()` to look up the raw bytes of any entry by its tag, or `adc_calibration()` and
+//! `ref_calibration()` to read the calibration constants directly.
+//!
+//! See the "Device Descriptors" section of the datasheet for the layout of each entry.
+
+/// Start address of the device descriptor table
+const TLV_START: usize = 0x1A00;
+/// First address past the end of the device descriptor table
+const TLV_END: usize = 0x1A80;
+/// Address of the first tag, after the info length, CRC, device ID and revision fields
+const FIRST_TAG: usize = TLV_START + 8;
+
+/// Tag of the die record entry
+pub const TAG_DIE_RECORD: u8 = 0x08;
+/// Tag of the ADC calibration entry
+pub const TAG_ADC_CAL: u8 = 0x11;
+/// Tag of the voltage reference calibration entry
+pub const TAG_REF_CAL: u8 = 0x12;
+/// Marks the end of the table
+const TAG_END: u8 = 0xFF;
+
+#[inline(always)]
+fn read_u8(addr: usize) -> u8 {
+    unsafe { core::ptr::read_volatile(addr as *const u8) }
+}
+
+#[inline(always)]
+fn read_u16(addr: usize) -> u16 {
+    unsafe { core::ptr::read_volatile(addr as *const u16) }
+}
+
+/// Device ID stored in the table header
+#[inline]
+pub fn device_id() -> u16 {
+    read_u16(TLV_START + 4)
+}
+
+/// Find the entry with the given tag and return its value bytes, or `None` if the tag isn't
+/// present. Entries that would extend past the end of the table are treated as absent.
+pub fn find(tag: u8) -> Option<&'static [u8]> {
+    let mut addr = FIRST_TAG;
+    while addr + 2 <= TLV_END {
+        let this_tag = read_u8(addr);
+        let len = read_u8(addr + 1) as usize;
+        let value = addr + 2;
+        if this_tag == TAG_END || value + len > TLV_END {
+            return None;
+        }
+        if this_tag == tag {
+            // The table lives in read-only information memory for the lifetime of the program
+            return Some(unsafe { core::slice::from_raw_parts(value as *const u8, len) });
+        }
+        addr = value + len;
+    }
+    None
+}
+
+/// Read the `idx`th little-endian word out of a TLV entry
+#[inline]
+fn word(value: &[u8], idx: usize) -> u16 {
+    u16::from_le_bytes([value[idx * 2], value[idx * 2 + 1]])
+}
+
+/// Factory ADC calibration constants
+#[derive(Clone, Copy, Debug)]
+pub struct AdcCalibration {
+    /// ADC gain factor, where 0x8000 represents a gain of 1
+    pub gain_factor: u16,
+    /// ADC offset, in ADC counts
+    pub offset: i16,
+    /// Temperature sensor reading at 30 °C, using the 1.5V internal reference
+    pub temp_30c_1v5: u16,
+    /// Temperature sensor reading at 85 °C, using the 1.5V internal reference
+    pub temp_85c_1v5: u16,
+}
+
+/// Read the ADC calibration constants, or `None` if the entry is missing or too short
+pub fn adc_calibration() -> Option<AdcCalibration> {
+    let value = find(TAG_ADC_CAL)?;
+    if value.len() < 8 {
+        return None;
+    }
+    Some(AdcCalibration {
+        gain_factor: word(value, 0),
+        offset: word(value, 1) as i16,
+        temp_30c_1v5: word(value, 2),
+        temp_85c_1v5: word(value, 3),
+    })
+}
+
+/// Factory voltage reference calibration constants. Each factor is the ratio of the actual to
+/// the nominal reference voltage, where 0x8000 represents a ratio of 1.
+#[derive(Clone, Copy, Debug)]
+pub struct RefCalibration {
+    /// Calibration factor of the 1.5V reference
+    pub factor_1v5: u16,
+    /// Calibration factor of the 2.0V reference
+    pub factor_2v0: u16,
+    /// Calibration factor of the 2.5V reference
+    pub factor_2v5: u16,
+}
+
+/// Read the voltage reference calibration constants, or `None` if the entry is missing or too
+/// short
+pub fn ref_calibration() -> Option<RefCalibration> {
+    let value = find(TAG_REF_CAL)?;
+    if value.len() < 6 {
+        return None;
+    }
+    Some(RefCalibration {
+        factor_1v5: word(value, 0),
+        factor_2v0: word(value, 1),
+        factor_2v5: word(value, 2),
+    })
+}
@@ -3,9 +3,18 @@
 //! **Note**: MSP430 devices will reset after bootup if watchdog is not stopped after an initial 32
 //! ms interval (roughly). If this is undesirable, call `Wdt::constrain()` as soon in the
 //! application as possible to stop the watchdog.
+//!
+//! Timeouts can be specified either as a raw `WdtClkPeriods` interval or in milliseconds via
+//! `Wdt::start_with_timeout()`, which picks the shortest interval that covers the requested
+//! duration for the currently selected clock source.
+//!
+//! Dropping a `Wdt` while the watchdog is running leaves it unable to be fed, so the device will
+//! reset once the interval expires. Debug builds panic when this happens. To stop the watchdog for
+//! good, use `Wdt::disable_permanently()` instead of dropping it.
 
-use crate::clock::{Aclk, Smclk};
+use crate::clock::{Aclk, Clock, Smclk, VLOCLK};
 use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
 use embedded_hal::timer::{Cancel, CountDown, Periodic};
 use embedded_hal::watchdog::{Watchdog, WatchdogDisable, WatchdogEnable};
 use msp430fr2355 as pac;
@@ -15,6 +24,22 @@ const PASSWORD: u8 = 0x5A;
 
 pub use pac::wdt_a::wdtctl::WDTIS_A as WdtClkPeriods;
 
+// Every selectable interval, from shortest to longest, along with its length as a power of 2
+const INTERVALS: [(u8, WdtClkPeriods); 8] = [
+    (6, WdtClkPeriods::_64),
+    (9, WdtClkPeriods::_512),
+    (13, WdtClkPeriods::_8192),
+    (15, WdtClkPeriods::_32K),
+    (19, WdtClkPeriods::_512K),
+    (23, WdtClkPeriods::_8192K),
+    (27, WdtClkPeriods::_128M),
+    (31, WdtClkPeriods::_2G),
+];
+
+/// Error returned when even the longest watchdog interval is shorter than the requested timeout
+#[derive(Clone, Copy, Debug)]
+pub struct TimeoutTooLong;
+
 mod sealed {
     use super::*;
 
@@ -28,6 +53,7 @@ mod sealed {
 pub struct Wdt<MODE> {
     _mode: PhantomData<MODE>,
     periph: pac::WDT_A,
+    clk_freq: u32,
 }
 
 impl Wdt<WatchdogMode> {
@@ -45,8 +71,29 @@ impl Wdt<WatchdogMode> {
         Wdt {
             _mode: PhantomData,
             periph: wdt,
+            clk_freq: VLOCLK as u32,
         }
     }
+
+    /// Whether the last reset was caused by a watchdog timeout. Call this at startup, before
+    /// switching to interval mode, which shares the same flag.
+    ///
+    /// This reads the watchdog interrupt flag rather than SYSRSTIV, so other pending reset causes
+    /// are left untouched. The flag stays set across resets until cleared with
+    /// `clear_reset_flag()`.
+    #[inline]
+    pub fn reset_was_watchdog(&self) -> bool {
+        let sfr = unsafe { &*pac::SFR::ptr() };
+        sfr.sfrifg1.read().wdtifg().bit_is_set()
+    }
+
+    /// Clear the flag read by `reset_was_watchdog()`, so that a later reset from another cause
+    /// isn't mistaken for a watchdog timeout.
+    #[inline]
+    pub fn clear_reset_flag(&mut self) {
+        let sfr = unsafe { &*pac::SFR::ptr() };
+        unsafe { sfr.sfrifg1.clear_bits(|w| w.wdtifg().clear_bit()) };
+    }
 }
 
 /// Watchdog mode typestate
@@ -84,7 +131,8 @@ impl<MODE: WatchdogSelect> Wdt<MODE> {
     }
 
     #[inline]
-    fn set_clk(&mut self, clk_src: WDTSSEL_A) -> &mut Self {
+    fn set_clk(&mut self, clk_src: WDTSSEL_A, clk_freq: u32) -> &mut Self {
+        self.clk_freq = clk_freq;
         // Halt timer first, as specified in the user's guide
         self.periph.wdtctl.write(|w| {
             Self::prewrite(w, 0)
@@ -107,20 +155,43 @@ impl<MODE: WatchdogSelect> Wdt<MODE> {
 
     /// Set watchdog clock source to ACLK and halt timer.
     #[inline]
-    pub fn set_aclk(&mut self, _clks: &Aclk) -> &mut Self {
-        self.set_clk(WDTSSEL_A::ACLK)
+    pub fn set_aclk(&mut self, aclk: &Aclk) -> &mut Self {
+        self.set_clk(WDTSSEL_A::ACLK, aclk.freq() as u32)
     }
 
     /// Set watchdog clock source to VLOCLK and halt timer.
     #[inline]
     pub fn set_vloclk(&mut self) -> &mut Self {
-        self.set_clk(WDTSSEL_A::VLOCLK)
+        self.set_clk(WDTSSEL_A::VLOCLK, VLOCLK as u32)
     }
 
     /// Set watchdog clock source to SMCLK and halt timer.
     #[inline]
-    pub fn set_smclk(&mut self, _clks: &Smclk) -> &mut Self {
-        self.set_clk(WDTSSEL_A::SMCLK)
+    pub fn set_smclk(&mut self, smclk: &Smclk) -> &mut Self {
+        self.set_clk(WDTSSEL_A::SMCLK, smclk.freq())
+    }
+
+    /// Find the shortest watchdog interval that lasts at least `timeout_ms` milliseconds when
+    /// running off the currently selected clock source.
+    #[inline]
+    pub fn timeout_periods(&self, timeout_ms: u32) -> Result<WdtClkPeriods, TimeoutTooLong> {
+        // Compare against the interval lengths scaled by 1000 to avoid dividing the frequency
+        let needed = timeout_ms as u64 * self.clk_freq as u64;
+        INTERVALS
+            .iter()
+            .find(|(exp, _)| (1000_u64 << exp) >= needed)
+            .map(|&(_, periods)| periods)
+            .ok_or(TimeoutTooLong)
+    }
+
+    /// Reset countdown and unpause timer using the shortest interval that lasts at least
+    /// `timeout_ms` milliseconds. If no interval is long enough the watchdog is left untouched
+    /// and an error is returned.
+    #[inline]
+    pub fn start_with_timeout(&mut self, timeout_ms: u32) -> Result<(), TimeoutTooLong> {
+        let periods = self.timeout_periods(timeout_ms)?;
+        self.unpause_and_set_time(periods);
+        Ok(())
     }
 
     // Reset countdown, unpause timer, and set timeout in a single write
@@ -144,6 +215,34 @@ impl<MODE: WatchdogSelect> Wdt<MODE> {
             .wdtctl
             .modify(|r, w| Self::prewrite(w, r.bits()).wdthold().hold());
     }
+
+    // Change the typestate without running `Drop`, which would see the watchdog mid-transition
+    #[inline(always)]
+    fn into_mode<NEW>(self) -> Wdt<NEW> {
+        let this = ManuallyDrop::new(self);
+        Wdt {
+            _mode: PhantomData,
+            periph: unsafe { core::ptr::read(&this.periph) },
+            clk_freq: this.clk_freq,
+        }
+    }
+
+    /// Stop the watchdog and give up the peripheral, so it can't be restarted. This is the
+    /// intended way to get rid of a `Wdt` whose watchdog may be running.
+    #[inline]
+    pub fn disable_permanently(mut self) {
+        self.pause();
+    }
+}
+
+impl<MODE> Drop for Wdt<MODE> {
+    fn drop(&mut self) {
+        let ctl = self.periph.wdtctl.read();
+        debug_assert!(
+            ctl.wdttmsel().bit_is_set() || ctl.wdthold().bit_is_set(),
+            "Wdt dropped while the watchdog is running, so it can no longer be fed"
+        );
+    }
 }
 
 impl Watchdog for Wdt<WatchdogMode> {
@@ -216,10 +315,7 @@ impl Wdt<WatchdogMode> {
     /// Convert to interval mode and pause timer
     #[inline]
     pub fn to_interval(self) -> Wdt<IntervalMode> {
-        let mut wdt = Wdt {
-            _mode: PhantomData,
-            periph: self.periph,
-        };
+        let mut wdt = self.into_mode();
         // Change mode bit and pause timer
         wdt.pause();
         wdt
@@ -230,10 +326,7 @@ impl Wdt<IntervalMode> {
     /// Convert to watchdog mode and pause timer
     #[inline]
     pub fn to_watchdog(self) -> Wdt<WatchdogMode> {
-        let mut wdt = Wdt {
-            _mode: PhantomData,
-            periph: self.periph,
-        };
+        let mut wdt: Wdt<WatchdogMode> = self.into_mode();
         // Change mode bit and pause timer
         wdt.pause();
         // Wipe out old interrupt flag, which may cause a watchdog reset